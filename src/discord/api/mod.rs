@@ -0,0 +1,136 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub const API_BASE: &str = "https://discord.com/api";
+
+/// Performs an authenticated GET against the REST API and parses the JSON
+/// body. `what` names the resource in the error message.
+pub async fn get<T: DeserializeOwned>(token: &str, path: &str, what: &str) -> Result<T, String> {
+    let request = reqwest::Client::new()
+        .get(format!("{}{}", API_BASE, path))
+        .header("Authorization", token)
+        .send()
+        .await;
+    match request {
+        Ok(response) => match response.json::<T>().await {
+            Ok(value) => Ok(value),
+            Err(err) => Err(format!("Failed to parse {}: {}", what, err)),
+        },
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Performs an authenticated POST with a JSON body, for endpoints whose
+/// response we do not need. Non-success statuses become errors carrying the
+/// response body, which is where Discord explains what was wrong.
+pub async fn post<B: Serialize>(token: &str, path: &str, body: &B) -> Result<(), String> {
+    let request = reqwest::Client::new()
+        .post(format!("{}{}", API_BASE, path))
+        .header("Authorization", token)
+        .json(body)
+        .send()
+        .await;
+    match request {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("{}: {}", status, body))
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Like [`post`], but parses the JSON response, for endpoints that return
+/// what they created.
+pub async fn create<B: Serialize, T: DeserializeOwned>(
+    token: &str,
+    path: &str,
+    body: &B,
+    what: &str,
+) -> Result<T, String> {
+    let request = reqwest::Client::new()
+        .post(format!("{}{}", API_BASE, path))
+        .header("Authorization", token)
+        .json(body)
+        .send()
+        .await;
+    match request {
+        Ok(response) if response.status().is_success() => match response.json::<T>().await {
+            Ok(value) => Ok(value),
+            Err(err) => Err(format!("Failed to parse {}: {}", what, err)),
+        },
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("{}: {}", status, body))
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Performs an authenticated PATCH with a JSON body and parses the updated
+/// resource Discord sends back.
+pub async fn patch<B: Serialize, T: DeserializeOwned>(
+    token: &str,
+    path: &str,
+    body: &B,
+    what: &str,
+) -> Result<T, String> {
+    let request = reqwest::Client::new()
+        .patch(format!("{}{}", API_BASE, path))
+        .header("Authorization", token)
+        .json(body)
+        .send()
+        .await;
+    match request {
+        Ok(response) if response.status().is_success() => match response.json::<T>().await {
+            Ok(value) => Ok(value),
+            Err(err) => Err(format!("Failed to parse {}: {}", what, err)),
+        },
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("{}: {}", status, body))
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Performs an authenticated PUT without a body, for endpoints that add
+/// something by URL alone.
+pub async fn put(token: &str, path: &str) -> Result<(), String> {
+    let request = reqwest::Client::new()
+        .put(format!("{}{}", API_BASE, path))
+        .header("Authorization", token)
+        .header("Content-Length", "0")
+        .send()
+        .await;
+    match request {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("{}: {}", status, body))
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Performs an authenticated DELETE.
+pub async fn delete(token: &str, path: &str) -> Result<(), String> {
+    let request = reqwest::Client::new()
+        .delete(format!("{}{}", API_BASE, path))
+        .header("Authorization", token)
+        .send()
+        .await;
+    match request {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("{}: {}", status, body))
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
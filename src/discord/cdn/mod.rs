@@ -0,0 +1,90 @@
+use super::snowflake::Snowflake;
+use super::types::StickerFormat;
+
+pub const CDN_BASE: &str = "https://cdn.discordapp.com";
+
+/// Hashes of animated images carry this prefix; they are also served as GIFs.
+fn extension(hash: &str) -> &'static str {
+    if hash.starts_with("a_") {
+        "gif"
+    } else {
+        "png"
+    }
+}
+
+/// A user's uploaded avatar. `size` should be a power of two between 16 and
+/// 4096.
+pub fn user_avatar(user_id: Snowflake, hash: &str, size: u32) -> String {
+    format!(
+        "{}/avatars/{}/{}.{}?size={}",
+        CDN_BASE,
+        user_id,
+        hash,
+        extension(hash),
+        size
+    )
+}
+
+/// The coloured Discord logo shown for users without an avatar. Users on the
+/// new username system have a discriminator of "0" and are bucketed by id
+/// instead.
+pub fn default_avatar(user_id: Snowflake, discriminator: &str) -> String {
+    let index = match discriminator.parse::<u64>() {
+        Ok(0) | Err(_) => (user_id.0 >> 22) % 6,
+        Ok(discriminator) => discriminator % 5,
+    };
+    format!("{}/embed/avatars/{}.png", CDN_BASE, index)
+}
+
+/// Whichever of [`user_avatar`] and [`default_avatar`] applies.
+pub fn avatar(user_id: Snowflake, discriminator: &str, hash: Option<&str>, size: u32) -> String {
+    match hash {
+        Some(hash) => user_avatar(user_id, hash, size),
+        None => default_avatar(user_id, discriminator),
+    }
+}
+
+pub fn guild_icon(guild_id: Snowflake, hash: &str, size: u32) -> String {
+    format!(
+        "{}/icons/{}/{}.{}?size={}",
+        CDN_BASE,
+        guild_id,
+        hash,
+        extension(hash),
+        size
+    )
+}
+
+pub fn emoji(emoji_id: Snowflake, animated: bool, size: u32) -> String {
+    let extension = if animated { "gif" } else { "png" };
+    format!(
+        "{}/emojis/{}.{}?size={}",
+        CDN_BASE, emoji_id, extension, size
+    )
+}
+
+/// Sticker images, or `None` for Lottie stickers, whose JSON we cannot draw.
+pub fn sticker(sticker_id: Snowflake, format: StickerFormat, size: u32) -> Option<String> {
+    let extension = match format {
+        StickerFormat::Png | StickerFormat::Apng => "png",
+        StickerFormat::Gif => "gif",
+        StickerFormat::Lottie | StickerFormat::Unknown(_) => return None,
+    };
+    // GIF stickers are only served from the media proxy.
+    let base = match format {
+        StickerFormat::Gif => "https://media.discordapp.net",
+        _ => CDN_BASE,
+    };
+    Some(format!(
+        "{}/stickers/{}.{}?size={}",
+        base, sticker_id, extension, size
+    ))
+}
+
+/// A group DM's custom icon.
+pub fn channel_icon(channel_id: Snowflake, hash: &str, size: u32) -> String {
+    format!(
+        "{}/channel-icons/{}/{}.png?size={}",
+        CDN_BASE, channel_id, hash, size
+    )
+}
@@ -0,0 +1,445 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::snowflake::Snowflake;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum OptionType {
+    SubCommand,
+    SubCommandGroup,
+    String,
+    Integer,
+    Boolean,
+    User,
+    Channel,
+    Role,
+    Mentionable,
+    Number,
+    Attachment,
+    Unknown(u64),
+}
+
+impl From<u64> for OptionType {
+    fn from(value: u64) -> Self {
+        match value {
+            1 => Self::SubCommand,
+            2 => Self::SubCommandGroup,
+            3 => Self::String,
+            4 => Self::Integer,
+            5 => Self::Boolean,
+            6 => Self::User,
+            7 => Self::Channel,
+            8 => Self::Role,
+            9 => Self::Mentionable,
+            10 => Self::Number,
+            11 => Self::Attachment,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<OptionType> for u64 {
+    fn from(value: OptionType) -> Self {
+        match value {
+            OptionType::SubCommand => 1,
+            OptionType::SubCommandGroup => 2,
+            OptionType::String => 3,
+            OptionType::Integer => 4,
+            OptionType::Boolean => 5,
+            OptionType::User => 6,
+            OptionType::Channel => 7,
+            OptionType::Role => 8,
+            OptionType::Mentionable => 9,
+            OptionType::Number => 10,
+            OptionType::Attachment => 11,
+            OptionType::Unknown(other) => other,
+        }
+    }
+}
+
+impl OptionType {
+    /// How the option is described next to its name in the composer.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SubCommand => "subcommand",
+            Self::SubCommandGroup => "subcommand group",
+            Self::String => "text",
+            Self::Integer => "integer",
+            Self::Boolean => "true/false",
+            Self::User => "user",
+            Self::Channel => "channel",
+            Self::Role => "role",
+            Self::Mentionable => "user or role",
+            Self::Number => "number",
+            Self::Attachment => "attachment",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApplicationCommand {
+    pub id: Snowflake,
+    pub application_id: Snowflake,
+    pub version: Snowflake,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub options: Vec<CommandOption>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommandOption {
+    #[serde(rename = "type")]
+    pub option_type: OptionType,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub choices: Vec<CommandChoice>,
+    /// A subcommand's options, or a subcommand group's subcommands.
+    #[serde(default)]
+    pub options: Vec<CommandOption>,
+}
+
+impl CommandOption {
+    pub fn is_subcommand(&self) -> bool {
+        matches!(
+            self.option_type,
+            OptionType::SubCommand | OptionType::SubCommandGroup
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommandChoice {
+    pub name: String,
+    pub value: Value,
+}
+
+/// What `/guilds/{id}/application-command-index` returns: every command the
+/// user can run in the guild, across all of its bots.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CommandIndex {
+    #[serde(default)]
+    pub application_commands: Vec<ApplicationCommand>,
+}
+
+/// One validated `name:value` pair, or a chosen subcommand with its own
+/// options, in the form the interaction carries.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InteractionOption {
+    #[serde(rename = "type")]
+    pub option_type: OptionType,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<InteractionOption>,
+}
+
+/// Splits `/name rest` into the command name and the text after it. `None`
+/// unless the input starts with `/`.
+pub fn parse_invocation(input: &str) -> Option<(&str, &str)> {
+    let rest = input.strip_prefix('/')?;
+    Some(split_word(rest))
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], text[end..].trim_start()),
+        None => (text, ""),
+    }
+}
+
+/// Splits `key:value key:"quoted value"` into raw arguments. An unquoted
+/// value runs until the next ` key:` naming one of `keys`, so values may
+/// contain colons themselves.
+fn parse_arguments<'a>(mut rest: &'a str, keys: &[&str]) -> Vec<(&'a str, &'a str)> {
+    let mut arguments = Vec::new();
+    while let Some(colon) = rest.find(':') {
+        let key = rest[..colon].trim();
+        let after = &rest[colon + 1..];
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => {
+                let end = next_key(after, keys).unwrap_or(after.len());
+                (after[..end].trim(), &after[end..])
+            }
+        };
+        arguments.push((key, value));
+        rest = remaining.trim_start();
+    }
+    arguments
+}
+
+/// Where the next ` key:` starts in `text`, if anywhere.
+fn next_key(text: &str, keys: &[&str]) -> Option<usize> {
+    let mut offset = 0;
+    for word in text.split(' ') {
+        if offset > 0 {
+            if let Some(colon) = word.find(':') {
+                if keys.contains(&&word[..colon]) {
+                    return Some(offset);
+                }
+            }
+        }
+        offset += word.len() + 1;
+    }
+    None
+}
+
+/// Follows the subcommands named at the start of `arguments` and returns the
+/// options that come next, with the `key:value` pairs given for them so far.
+/// Stops at the first word that is not one of the subcommands, so they are
+/// what is returned while one is being typed.
+pub fn current_options<'a>(
+    command: &'a ApplicationCommand,
+    mut arguments: &'a str,
+) -> (&'a [CommandOption], Vec<(&'a str, &'a str)>) {
+    let mut defined = command.options.as_slice();
+    while defined.iter().any(CommandOption::is_subcommand) {
+        let (name, rest) = split_word(arguments);
+        match defined.iter().find(|o| o.is_subcommand() && o.name == name) {
+            // Only once a space shows the name is finished.
+            Some(subcommand) if arguments.len() > name.len() => {
+                defined = &subcommand.options;
+                arguments = rest;
+            }
+            _ => return (defined, Vec::new()),
+        }
+    }
+    let keys: Vec<&str> = defined.iter().map(|o| o.name.as_str()).collect();
+    (defined, parse_arguments(arguments, &keys))
+}
+
+/// Checks the text after the command name against its options, converting
+/// each value to the type the option expects. Subcommands (and groups) are
+/// named first, as in `/role add user:@someone`. Errors name the first
+/// problem found.
+pub fn validate(
+    command: &ApplicationCommand,
+    arguments: &str,
+) -> Result<Vec<InteractionOption>, String> {
+    validate_options(&command.name, &command.options, arguments)
+}
+
+fn validate_options(
+    path: &str,
+    defined: &[CommandOption],
+    text: &str,
+) -> Result<Vec<InteractionOption>, String> {
+    if defined.iter().any(CommandOption::is_subcommand) {
+        let (name, rest) = split_word(text);
+        let names: Vec<&str> = defined.iter().map(|o| o.name.as_str()).collect();
+        let subcommand = defined
+            .iter()
+            .find(|o| o.is_subcommand() && o.name == name)
+            .ok_or_else(|| format!("/{} needs one of: {}", path, names.join(", ")))?;
+        let path = format!("{} {}", path, subcommand.name);
+        return Ok(vec![InteractionOption {
+            option_type: subcommand.option_type,
+            name: subcommand.name.clone(),
+            value: None,
+            options: validate_options(&path, &subcommand.options, rest)?,
+        }]);
+    }
+    let keys: Vec<&str> = defined.iter().map(|o| o.name.as_str()).collect();
+    let mut options = Vec::new();
+    for (key, raw) in parse_arguments(text, &keys) {
+        let option = defined
+            .iter()
+            .find(|o| o.name == key)
+            .ok_or_else(|| format!("/{} has no option \"{}\"", path, key))?;
+        if options
+            .iter()
+            .any(|o: &InteractionOption| o.name == option.name)
+        {
+            return Err(format!("\"{}\" was given twice", key));
+        }
+        options.push(InteractionOption {
+            option_type: option.option_type,
+            name: option.name.clone(),
+            value: Some(convert(option, raw)?),
+            options: Vec::new(),
+        });
+    }
+    for option in defined {
+        if option.required && !options.iter().any(|o| o.name == option.name) {
+            return Err(format!("\"{}\" is required", option.name));
+        }
+    }
+    Ok(options)
+}
+
+fn convert(option: &CommandOption, raw: &str) -> Result<Value, String> {
+    if !option.choices.is_empty() {
+        return option
+            .choices
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(raw) || value_matches(&c.value, raw))
+            .map(|c| c.value.clone())
+            .ok_or_else(|| {
+                let names: Vec<&str> = option.choices.iter().map(|c| c.name.as_str()).collect();
+                format!("\"{}\" must be one of: {}", option.name, names.join(", "))
+            });
+    }
+    let invalid = || {
+        format!(
+            "\"{}\" must be a {}",
+            option.name,
+            option.option_type.name()
+        )
+    };
+    match option.option_type {
+        OptionType::String => Ok(Value::from(raw)),
+        OptionType::Integer => raw.parse::<i64>().map(Value::from).map_err(|_| invalid()),
+        OptionType::Number => raw.parse::<f64>().map(Value::from).map_err(|_| invalid()),
+        OptionType::Boolean => match raw.to_ascii_lowercase().as_str() {
+            "true" | "yes" => Ok(Value::from(true)),
+            "false" | "no" => Ok(Value::from(false)),
+            _ => Err(invalid()),
+        },
+        OptionType::User | OptionType::Channel | OptionType::Role | OptionType::Mentionable => {
+            mention_id(raw)
+                .map(|id| Value::from(id.to_string()))
+                .ok_or_else(invalid)
+        }
+        _ => Err(format!(
+            "\"{}\" options cannot be entered here",
+            option.option_type.name()
+        )),
+    }
+}
+
+fn value_matches(value: &Value, raw: &str) -> bool {
+    match value {
+        Value::String(value) => value == raw,
+        other => serde_json::from_str::<Value>(raw).is_ok_and(|raw| &raw == other),
+    }
+}
+
+/// Accepts a bare id or a `<@id>`, `<@!id>`, `<@&id>` or `<#id>` mention.
+fn mention_id(raw: &str) -> Option<Snowflake> {
+    let inner = raw
+        .strip_prefix('<')
+        .and_then(|r| r.strip_suffix('>'))
+        .map(|r| r.trim_start_matches(['@', '!', '&', '#']))
+        .unwrap_or(raw);
+    inner.parse::<u64>().ok().map(Snowflake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command() -> ApplicationCommand {
+        serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "application_id": "2",
+            "version": "3",
+            "name": "ban",
+            "description": "Ban someone",
+            "options": [
+                {"type": 6, "name": "user", "description": "Who", "required": true},
+                {"type": 3, "name": "reason", "description": "Why"},
+                {"type": 4, "name": "days", "description": "History to delete",
+                 "choices": [{"name": "None", "value": 0}, {"name": "Week", "value": 7}]}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_quoted_and_unquoted_arguments() {
+        let (name, rest) =
+            parse_invocation("/ban user:<@42> reason:spamming links days:\"Week\"").unwrap();
+        assert_eq!(name, "ban");
+        assert_eq!(
+            parse_arguments(rest, &["user", "reason", "days"]),
+            vec![
+                ("user", "<@42>"),
+                ("reason", "spamming links"),
+                ("days", "Week")
+            ]
+        );
+        assert_eq!(parse_invocation("/ping"), Some(("ping", "")));
+        assert_eq!(parse_invocation("ping"), None);
+    }
+
+    #[test]
+    fn only_splits_on_option_names() {
+        assert_eq!(
+            parse_arguments(
+                "reason:see https://x.io note: later user:1",
+                &["user", "reason"]
+            ),
+            vec![("reason", "see https://x.io note: later"), ("user", "1")]
+        );
+    }
+
+    #[test]
+    fn validates_against_options() {
+        let command = command();
+        let options = validate(&command, "user:<@!42> days:week").unwrap();
+        assert_eq!(options[0].value, Some(Value::from("42")));
+        assert_eq!(options[1].value, Some(Value::from(7)));
+
+        assert!(validate(&command, "reason:x")
+            .unwrap_err()
+            .contains("required"));
+        assert!(validate(&command, "user:someone")
+            .unwrap_err()
+            .contains("must be a user"));
+        assert!(validate(&command, "user:1 days:3")
+            .unwrap_err()
+            .contains("one of"));
+        assert!(validate(&command, "colour:red user:1")
+            .unwrap_err()
+            .contains("no option"));
+    }
+
+    #[test]
+    fn nests_subcommand_options() {
+        let command: ApplicationCommand = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "application_id": "2",
+            "version": "3",
+            "name": "role",
+            "description": "Manage roles",
+            "options": [
+                {"type": 2, "name": "member", "description": "Member roles", "options": [
+                    {"type": 1, "name": "add", "description": "Give a role", "options": [
+                        {"type": 6, "name": "user", "description": "Who", "required": true}
+                    ]}
+                ]},
+                {"type": 1, "name": "list", "description": "List roles"}
+            ]
+        }))
+        .unwrap();
+        let options = validate(&command, "member add user:42").unwrap();
+        assert_eq!(
+            serde_json::to_value(&options).unwrap(),
+            serde_json::json!([{"type": 2, "name": "member", "options": [
+                {"type": 1, "name": "add", "options": [
+                    {"type": 6, "name": "user", "value": "42"}
+                ]}
+            ]}])
+        );
+        assert_eq!(
+            serde_json::to_value(validate(&command, "list").unwrap()).unwrap(),
+            serde_json::json!([{"type": 1, "name": "list"}])
+        );
+        assert!(validate(&command, "member")
+            .unwrap_err()
+            .contains("/role member needs one of: add"));
+        assert!(validate(&command, "member add")
+            .unwrap_err()
+            .contains("required"));
+    }
+}
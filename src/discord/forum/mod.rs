@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use super::snowflake::Snowflake;
+use super::threads::Thread;
+
+/// Channel flag for forums whose posts must carry at least one tag.
+pub const REQUIRE_TAG: u64 = 1 << 4;
+
+/// A tag posts in a forum can be filed under. Moderated tags can only be
+/// applied by members who can manage threads.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForumTag {
+    pub id: Snowflake,
+    pub name: String,
+    #[serde(default)]
+    pub moderated: bool,
+    pub emoji_id: Option<Snowflake>,
+    /// A standard emoji shown before the name.
+    pub emoji_name: Option<String>,
+}
+
+impl ForumTag {
+    pub fn label(&self) -> String {
+        match &self.emoji_name {
+            Some(emoji) => format!("{} {}", emoji, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    LatestActivity,
+    CreationDate,
+}
+
+impl SortOrder {
+    /// The order a forum asks for in its `default_sort_order`.
+    pub fn from_channel(default_sort_order: Option<u64>) -> Self {
+        match default_sort_order {
+            Some(1) => Self::CreationDate,
+            _ => Self::LatestActivity,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LatestActivity => "Recently Active",
+            Self::CreationDate => "Date Posted",
+        }
+    }
+}
+
+/// When a post last saw a message; posts without replies count from their
+/// creation.
+pub fn last_activity(post: &Thread) -> Snowflake {
+    post.last_message_id.unwrap_or(post.id)
+}
+
+/// The posts to list, with `tag` (if any) applied as a filter, newest first
+/// by `sort`.
+pub fn arrange<'a>(
+    posts: impl IntoIterator<Item = &'a Thread>,
+    sort: SortOrder,
+    tag: Option<Snowflake>,
+) -> Vec<&'a Thread> {
+    let mut posts: Vec<&Thread> = posts
+        .into_iter()
+        .filter(|p| tag.is_none_or(|tag| p.applied_tags.contains(&tag)))
+        .collect();
+    match sort {
+        SortOrder::LatestActivity => posts.sort_by_key(|p| std::cmp::Reverse(last_activity(p))),
+        // A thread's id is its creation time.
+        SortOrder::CreationDate => posts.sort_by_key(|p| std::cmp::Reverse(p.id)),
+    }
+    posts
+}
+
+/// Starts a post: a thread whose first message is `body`.
+pub async fn create_post(
+    token: &str,
+    channel_id: Snowflake,
+    title: &str,
+    body: &str,
+    tags: &[Snowflake],
+) -> Result<Thread, String> {
+    super::api::create(
+        token,
+        &format!("/channels/{}/threads", channel_id),
+        &serde_json::json!({
+            "name": title,
+            "applied_tags": tags,
+            "message": { "content": body },
+        }),
+        "post",
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discord::threads::tests::thread;
+
+    fn post(id: u64, last_message: Option<u64>, tags: &[&str]) -> Thread {
+        let mut post = thread(id, 2, last_message, false);
+        post["applied_tags"] = serde_json::json!(tags);
+        serde_json::from_value(post).unwrap()
+    }
+
+    #[test]
+    fn sorts_and_filters_posts() {
+        let posts = [
+            post(10, Some(40), &["5"]),
+            post(20, None, &[]),
+            post(30, Some(35), &["5", "6"]),
+        ];
+        let ids = |posts: Vec<&Thread>| posts.iter().map(|p| p.id.0).collect::<Vec<_>>();
+        assert_eq!(
+            ids(arrange(&posts, SortOrder::LatestActivity, None)),
+            [10, 30, 20]
+        );
+        assert_eq!(
+            ids(arrange(&posts, SortOrder::CreationDate, None)),
+            [30, 20, 10]
+        );
+        assert_eq!(
+            ids(arrange(&posts, SortOrder::CreationDate, Some(Snowflake(5)))),
+            [30, 10]
+        );
+    }
+}
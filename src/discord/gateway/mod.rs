@@ -2,7 +2,7 @@ use std::sync::mpsc;
 // use std::sync::Arc;
 use std::thread::sleep;
 use std::thread::spawn;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_native_tls::TlsStream;
 use async_std::net::TcpStream;
@@ -20,6 +20,123 @@ use futures_util::StreamExt;
 use serde::Deserialize;
 use serde::Serialize;
 
+type GatewayStream = WebSocketStream<Stream<TcpStream, TlsStream<TcpStream>>>;
+
+type Waker = Box<dyn Fn() + Send>;
+
+lazy_static::lazy_static! {
+    static ref SESSION: std::sync::Mutex<Session> = std::sync::Mutex::new(Session::default());
+    static ref WAKER: std::sync::Mutex<Option<Waker>> = std::sync::Mutex::new(None);
+}
+
+/// How often the socket thread wakes to send queued commands.
+const COMMAND_POLL: Duration = Duration::from_millis(50);
+
+/// The events a bot session asks for: guilds, members and presences (which
+/// op 8 needs), messages with their content, and typing. User sessions send
+/// no intents and get everything.
+const BOT_INTENTS: u64 = 1 << 0 // GUILDS
+    | 1 << 1 // GUILD_MEMBERS
+    | 1 << 8 // GUILD_PRESENCES
+    | 1 << 9 // GUILD_MESSAGES
+    | 1 << 11 // GUILD_MESSAGE_TYPING
+    | 1 << 12 // DIRECT_MESSAGES
+    | 1 << 14 // DIRECT_MESSAGE_TYPING
+    | 1 << 15; // MESSAGE_CONTENT
+
+/// The intents that have to be enabled for the bot in the developer portal.
+/// Without them Discord closes the socket with 4014, and the session
+/// identifies again with the rest.
+const PRIVILEGED_INTENTS: u64 = 1 << 1 // GUILD_MEMBERS
+    | 1 << 8 // GUILD_PRESENCES
+    | 1 << 15; // MESSAGE_CONTENT
+
+/// Close code for intents the bot is not allowed to ask for.
+const DISALLOWED_INTENTS: u16 = 4014;
+
+/// One run of `GatewayClient::start`. Starting or shutting down replaces it,
+/// so events and commands never carry over from one session to the next,
+/// and the old session's threads stop once they see the generation change.
+#[derive(Default)]
+struct Session {
+    generation: u64,
+    id: Option<String>,
+    events: Vec<DispatchEvent>,
+    commands: Vec<String>,
+    warning: Option<String>,
+}
+
+/// Ends the current session and starts an empty one, returning its
+/// generation.
+fn replace_session() -> u64 {
+    let mut session = SESSION.lock().unwrap();
+    let generation = session.generation + 1;
+    *session = Session {
+        generation,
+        ..Session::default()
+    };
+    generation
+}
+
+/// A dispatch (op 0) from the gateway, such as MESSAGE_CREATE. The data is
+/// left as JSON for the receiver to parse into its own types.
+#[derive(Debug)]
+pub struct DispatchEvent {
+    pub name: String,
+    pub data: serde_json::Value,
+}
+
+/// Takes every event received since the last call, oldest first.
+pub fn drain_events() -> Vec<DispatchEvent> {
+    std::mem::take(&mut SESSION.lock().unwrap().events)
+}
+
+/// Registers a callback run whenever an event is queued, so the UI can
+/// repaint and drain it.
+pub fn set_waker(waker: impl Fn() + Send + 'static) {
+    *WAKER.lock().unwrap() = Some(Box::new(waker));
+}
+
+/// The id of the current gateway session, which REST calls that act on its
+/// behalf (such as interactions) have to name. `None` until READY arrives.
+pub fn session_id() -> Option<String> {
+    SESSION.lock().unwrap().id.clone()
+}
+
+/// Something the user should know about the current session that did not
+/// stop it, such as privileged intents the bot was refused.
+pub fn warning() -> Option<String> {
+    SESSION.lock().unwrap().warning.clone()
+}
+
+/// Queues a command (such as op 8 or op 14) for the gateway. Commands are
+/// held until the session is ready, then sent in order.
+pub fn send_command(op: u8, data: serde_json::Value) {
+    let payload = serde_json::json!({ "op": op, "d": data });
+    SESSION.lock().unwrap().commands.push(payload.to_string());
+}
+
+/// Closes the current session's socket and drops anything it queued. Its
+/// `start` returns `Ok` once the socket has closed.
+pub fn shutdown() {
+    replace_session();
+}
+
+/// Whether `generation` has been replaced by a newer session.
+fn is_stale(generation: u64) -> bool {
+    SESSION.lock().unwrap().generation != generation
+}
+
+/// Why a connection ended.
+enum Closed {
+    /// `shutdown` was called or another session started.
+    Replaced,
+    /// Discord closed the socket with this code and reason.
+    Code(u16, String),
+    /// The connection failed or dropped.
+    Error(String),
+}
+
 enum ThreadEvent {
     SendMessage(String),
     Abort,
@@ -65,6 +182,20 @@ struct HelloData {
     heartbeat_interval: u64,
 }
 
+/// Any dispatch from the gateway, with the event-specific data left unparsed.
+#[derive(Debug, Deserialize)]
+struct ReceivedPayload {
+    op: u8,
+    t: Option<String>,
+    #[serde(default)]
+    d: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadyData {
+    session_id: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct IdentifyPayload {
     op: u8,
@@ -74,7 +205,8 @@ struct IdentifyPayload {
 #[derive(Debug, Serialize, Deserialize)]
 struct IdentifyData {
     token: String,
-    intents: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intents: Option<u64>,
     properties: IdentifyProperties,
 }
 
@@ -91,7 +223,7 @@ struct IdentifyProperties {
 pub struct GatewayClient {
     token: String,
     heartbeat_interval: u64,
-    receiver: Option<SplitStream<WebSocketStream<Stream<TcpStream, TlsStream<TcpStream>>>>>,
+    receiver: Option<SplitStream<GatewayStream>>,
     thread_sender: Option<mpsc::Sender<ThreadEvent>>,
 }
 
@@ -105,36 +237,71 @@ impl GatewayClient {
         }
     }
 
-    pub async fn start(&mut self) {
-        let (ws_stream, _) = connect_async("wss://gateway.discord.gg/?v=9&encoding=json")
-            .await
-            .unwrap();
+    /// Runs a session until `shutdown` is called, which returns `Ok`, or until
+    /// the connection is lost, which returns why.
+    pub async fn start(&mut self) -> Result<(), String> {
+        let generation = replace_session();
+        // Bot tokens carry a "Bot " prefix for REST, but identify without it
+        // and must name their intents.
+        let (token, mut intents) = match self.token.strip_prefix("Bot ") {
+            Some(token) => (token.to_string(), Some(BOT_INTENTS)),
+            None => (self.token.clone(), None),
+        };
+        loop {
+            match self.connect(generation, &token, intents).await {
+                Closed::Replaced => return Ok(()),
+                Closed::Code(DISALLOWED_INTENTS, _) if intents == Some(BOT_INTENTS) => {
+                    let mut session = SESSION.lock().unwrap();
+                    if session.generation != generation {
+                        return Ok(());
+                    }
+                    session.warning = Some(
+                        "This bot is not allowed the members, presences or message content intents, so the member list, statuses and message text may be missing".to_string(),
+                    );
+                    intents = Some(BOT_INTENTS & !PRIVILEGED_INTENTS);
+                }
+                Closed::Code(code, reason) => {
+                    return Err(format!("Gateway closed with code {}: {}", code, reason))
+                }
+                Closed::Error(why) => return Err(why),
+            }
+        }
+    }
+
+    /// Connects, identifies and reads dispatches until the socket closes or
+    /// the session is replaced.
+    async fn connect(&mut self, generation: u64, token: &str, intents: Option<u64>) -> Closed {
+        let (ws_stream, _) = match connect_async("wss://gateway.discord.gg/?v=9&encoding=json").await {
+            Ok(connection) => connection,
+            Err(why) => return Closed::Error(format!("Failed to connect to gateway: {}", why)),
+        };
         let (write, mut read) = ws_stream.split();
-        let hello = read.next().await.unwrap().unwrap();
-        if let Text(hello) = hello {
-            let json = serde_json::from_str::<HelloPayload>(&hello).unwrap();
-            self.heartbeat_interval = json.d.heartbeat_interval;
-        } else {
-            panic!("Expected hello message");
+        match read.next().await {
+            Some(Ok(Text(hello))) => match serde_json::from_str::<HelloPayload>(&hello) {
+                Ok(json) => self.heartbeat_interval = json.d.heartbeat_interval,
+                Err(why) => return Closed::Error(format!("Failed to parse hello: {}", why)),
+            },
+            _ => return Closed::Error("Expected hello message".to_string()),
         }
 
         let (tx, rx) = mpsc::channel();
-        let interval = self.heartbeat_interval.clone();
-        spawn(move || socket(interval, write, rx));
+        let interval = self.heartbeat_interval;
+        spawn(move || socket(generation, interval, write, rx));
         self.receiver = Some(read);
         self.thread_sender = Some(tx);
 
-        let identify = serde_json::json!({
-            "op": 2,
-            "d": {
-                "token": self.token,
-                "properties": {
-                    "$os": "linux",
-                    "$browser": "discord-rs",
-                    "$device": "discord-rs",
+        let identify = IdentifyPayload {
+            op: 2,
+            d: IdentifyData {
+                token: token.to_string(),
+                intents,
+                properties: IdentifyProperties {
+                    os: "linux".to_string(),
+                    browser: "discord-rs".to_string(),
+                    device: "discord-rs".to_string(),
                 },
-            }
-        });
+            },
+        };
         self.thread_sender
             .as_ref()
             .unwrap()
@@ -142,14 +309,30 @@ impl GatewayClient {
                 serde_json::to_string(&identify).unwrap(),
             ))
             .unwrap();
-        loop {
+        let closed = loop {
             let message = self.receiver.as_mut().unwrap().next().await;
-            if let Some(message) = message {
-                if let Text(message) = message.unwrap() {
+            if is_stale(generation) {
+                break Closed::Replaced;
+            }
+            match message {
+                Some(Ok(Text(message))) => {
                     println!("{}", message);
+                    if let Ok(payload) = serde_json::from_str::<ReceivedPayload>(&message) {
+                        handle_dispatch(generation, payload);
+                    }
                 }
+                Some(Ok(Message::Close(Some(frame)))) => {
+                    break Closed::Code(frame.code.into(), frame.reason.into_owned())
+                }
+                Some(Ok(_)) => (),
+                Some(Err(why)) => break Closed::Error(format!("Gateway error: {}", why)),
+                None => break Closed::Error("Gateway connection closed".to_string()),
             }
-        }
+        };
+        // The socket thread may already be gone if it closed the socket.
+        let _ = self.thread_sender.take().unwrap().send(ThreadEvent::Abort);
+        self.receiver = None;
+        closed
     }
 
     // pub fn get_message(&mut self) -> Option<Message> {
@@ -162,12 +345,38 @@ impl GatewayClient {
     // }
 }
 
+fn handle_dispatch(generation: u64, payload: ReceivedPayload) {
+    let name = match (payload.op, payload.t) {
+        (0, Some(name)) => name,
+        _ => return,
+    };
+    let mut session = SESSION.lock().unwrap();
+    if session.generation != generation {
+        return;
+    }
+    if name == "READY" {
+        if let Ok(ready) = serde_json::from_value::<ReadyData>(payload.d.clone()) {
+            session.id = Some(ready.session_id);
+        }
+    }
+    session.events.push(DispatchEvent {
+        name,
+        data: payload.d,
+    });
+    drop(session);
+    if let Some(waker) = WAKER.lock().unwrap().as_ref() {
+        waker();
+    }
+}
+
 fn socket(
+    generation: u64,
     interval: u64,
-    mut socket_sender: SplitSink<WebSocketStream<Stream<TcpStream, TlsStream<TcpStream>>>, Message>,
+    mut socket_sender: SplitSink<GatewayStream, Message>,
     thread_receiver: mpsc::Receiver<ThreadEvent>,
 ) {
     async_std::task::block_on(async move {
+        let mut last_heartbeat = Instant::now();
         'outer: loop {
             loop {
                 match thread_receiver.try_recv() {
@@ -182,7 +391,35 @@ fn socket(
                     Err(mpsc::TryRecvError::Disconnected) => break 'outer,
                 }
             }
-            sleep(Duration::from_millis(interval));
+            let commands = {
+                let mut session = SESSION.lock().unwrap();
+                if session.generation != generation {
+                    None
+                } else if session.id.is_some() {
+                    Some(std::mem::take(&mut session.commands))
+                } else {
+                    // Anything sent before READY would arrive ahead of identify.
+                    Some(Vec::new())
+                }
+            };
+            let commands = match commands {
+                Some(commands) => commands,
+                None => {
+                    // Closing makes Discord close back, which ends the reader.
+                    let _ = socket_sender.close().await;
+                    break 'outer;
+                }
+            };
+            for command in commands {
+                if let Err(why) = socket_sender.send(Text(command)).await {
+                    println!("Error sending gateway command: {:?}", why);
+                }
+            }
+            sleep(COMMAND_POLL);
+            if last_heartbeat.elapsed() < Duration::from_millis(interval) {
+                continue;
+            }
+            last_heartbeat = Instant::now();
             println!("Sending heartbeat");
             match socket_sender.send(Text("{\"op\": 1, \"d\": null}".to_string())).await {
                 Ok(()) => (),
@@ -0,0 +1,142 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use super::commands::{ApplicationCommand, InteractionOption};
+use super::snowflake::Snowflake;
+use super::types::ComponentType;
+
+/// Interaction types a client can send.
+const APPLICATION_COMMAND: u64 = 2;
+const MESSAGE_COMPONENT: u64 = 3;
+
+/// Slash commands, as opposed to user or message context menu commands.
+const CHAT_INPUT: u64 = 1;
+
+/// What the user did to a component, as seen from the message it belongs to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentClick {
+    pub application_id: Snowflake,
+    /// Only known here for messages from outside a channel's history, such
+    /// as the mentions inbox; otherwise the guild owning the channel.
+    pub guild_id: Option<Snowflake>,
+    pub channel_id: Snowflake,
+    pub message_id: Snowflake,
+    pub message_flags: u64,
+    pub component_type: ComponentType,
+    pub custom_id: String,
+    /// The chosen option values, for select menus.
+    pub values: Vec<String>,
+}
+
+/// Something sent to a bot on the user's behalf. The bot answers by editing a
+/// message or posting a new one, which arrives over the gateway like any
+/// other message.
+#[derive(Debug, Serialize)]
+pub struct Interaction<D> {
+    #[serde(rename = "type")]
+    interaction_type: u64,
+    nonce: Snowflake,
+    application_id: Snowflake,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guild_id: Option<Snowflake>,
+    channel_id: Snowflake,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<Snowflake>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_flags: Option<u64>,
+    session_id: String,
+    data: D,
+}
+
+/// INTERACTION_SUCCESS or INTERACTION_FAILURE, sent once the bot has
+/// acknowledged (or failed to acknowledge) an interaction we sent.
+#[derive(Clone, Debug, Deserialize)]
+pub struct InteractionResult {
+    pub nonce: Option<Snowflake>,
+}
+
+/// A click on a button or a choice in a select menu.
+pub type ComponentInteraction = Interaction<ComponentData>;
+
+/// A slash command run from the composer.
+pub type CommandInteraction = Interaction<CommandData>;
+
+#[derive(Debug, Serialize)]
+pub struct ComponentData {
+    component_type: ComponentType,
+    custom_id: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    values: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommandData {
+    id: Snowflake,
+    version: Snowflake,
+    name: String,
+    #[serde(rename = "type")]
+    command_type: u64,
+    options: Vec<InteractionOption>,
+    attachments: Vec<()>,
+}
+
+impl ComponentInteraction {
+    pub fn new(click: ComponentClick, guild_id: Option<Snowflake>, session_id: String) -> Self {
+        Self {
+            interaction_type: MESSAGE_COMPONENT,
+            nonce: Snowflake::from_time(SystemTime::now()),
+            application_id: click.application_id,
+            guild_id,
+            channel_id: click.channel_id,
+            message_id: Some(click.message_id),
+            message_flags: Some(click.message_flags),
+            session_id,
+            data: ComponentData {
+                component_type: click.component_type,
+                custom_id: click.custom_id,
+                values: click.values,
+            },
+        }
+    }
+}
+
+impl CommandInteraction {
+    pub fn new(
+        command: &ApplicationCommand,
+        options: Vec<InteractionOption>,
+        guild_id: Option<Snowflake>,
+        channel_id: Snowflake,
+        session_id: String,
+    ) -> Self {
+        Self {
+            interaction_type: APPLICATION_COMMAND,
+            nonce: Snowflake::from_time(SystemTime::now()),
+            application_id: command.application_id,
+            guild_id,
+            channel_id,
+            message_id: None,
+            message_flags: None,
+            session_id,
+            data: CommandData {
+                id: command.id,
+                version: command.version,
+                name: command.name.clone(),
+                command_type: CHAT_INPUT,
+                options,
+                attachments: Vec::new(),
+            },
+        }
+    }
+}
+
+impl<D: Serialize> Interaction<D> {
+    /// Echoed back in INTERACTION_SUCCESS and INTERACTION_FAILURE.
+    pub fn nonce(&self) -> Snowflake {
+        self.nonce
+    }
+
+    pub async fn send(&self, token: &str) -> Result<(), String> {
+        super::api::post(token, "/interactions", self).await
+    }
+}
@@ -0,0 +1,911 @@
+use std::collections::HashMap;
+
+use super::snowflake::Snowflake;
+
+/// A block-level element of a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    Heading {
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Quote(Vec<Block>),
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    },
+    List {
+        /// The number of the first item for ordered lists.
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListItem {
+    pub content: Vec<Inline>,
+    /// Nested lists indented under this item.
+    pub children: Vec<Block>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inline {
+    Text(String),
+    Bold(Vec<Inline>),
+    Italic(Vec<Inline>),
+    Underline(Vec<Inline>),
+    Strikethrough(Vec<Inline>),
+    Spoiler(Vec<Inline>),
+    Code(String),
+    /// A masked link, `[text](url)`.
+    Link {
+        text: Vec<Inline>,
+        url: String,
+    },
+    /// A bare URL, optionally wrapped in `<>` to suppress its embed.
+    Url(String),
+    /// `<@id>` or `<@!id>`.
+    UserMention(Snowflake),
+    /// `<#id>`.
+    ChannelMention(Snowflake),
+    /// `<@&id>`.
+    RoleMention(Snowflake),
+    /// `@everyone` or `@here`, without the `@`.
+    MassMention(String),
+    /// `<:name:id>`, or `<a:name:id>` when animated.
+    Emoji {
+        name: String,
+        id: Snowflake,
+        animated: bool,
+    },
+    /// `<t:unix>` or `<t:unix:style>`, shown in the reader's local time.
+    Timestamp {
+        unix: i64,
+        style: Option<char>,
+    },
+    LineBreak,
+}
+
+/// Parses Discord-flavoured markdown. Never fails: anything that does not
+/// form valid markup is kept as text.
+pub fn parse(input: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut rest = input;
+    while let Some((before, block, after)) = split_code_block(rest) {
+        parse_lines(before, false, &mut blocks);
+        blocks.push(block);
+        rest = after;
+    }
+    parse_lines(rest, false, &mut blocks);
+    blocks
+}
+
+/// Finds the first fenced code block, returning the text before it, the
+/// block itself and the text after it.
+fn split_code_block(input: &str) -> Option<(&str, Block, &str)> {
+    let mut search = 0;
+    loop {
+        let start = search + input[search..].find("```")?;
+        let body_start = start + 3;
+        let end = body_start + input[body_start..].find("```")?;
+        let body = &input[body_start..end];
+        if body.trim().is_empty() {
+            // "``````" is not a code block; look for the next fence instead.
+            search = end + 3;
+            continue;
+        }
+        let (language, code) = match body.split_once('\n') {
+            Some((first, code)) if is_language_tag(first) => (Some(first.to_string()), code),
+            _ => (None, body),
+        };
+        let code = code.strip_prefix('\n').unwrap_or(code);
+        let code = code.strip_suffix('\n').unwrap_or(code);
+        let block = Block::CodeBlock {
+            language,
+            code: code.to_string(),
+        };
+        return Some((&input[..start], block, &input[end + 3..]));
+    }
+}
+
+fn is_language_tag(line: &str) -> bool {
+    !line.is_empty()
+        && line
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-_#.".contains(c))
+}
+
+struct ListEntry<'a> {
+    indent: usize,
+    number: Option<u64>,
+    text: &'a str,
+}
+
+fn parse_lines(text: &str, in_quote: bool, blocks: &mut Vec<Block>) {
+    if text.is_empty() {
+        return;
+    }
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+
+        if !in_quote {
+            if let Some(rest) = line.strip_prefix(">>> ") {
+                flush_paragraph(&mut paragraph, blocks);
+                let mut quoted = vec![rest];
+                quoted.extend(&lines[i + 1..]);
+                let mut inner = Vec::new();
+                parse_lines(&quoted.join("\n"), true, &mut inner);
+                blocks.push(Block::Quote(inner));
+                return;
+            }
+            if quote_line(line).is_some() {
+                flush_paragraph(&mut paragraph, blocks);
+                let mut quoted = Vec::new();
+                while let Some(rest) = lines.get(i).and_then(|l| quote_line(l)) {
+                    quoted.push(rest);
+                    i += 1;
+                }
+                let mut inner = Vec::new();
+                parse_lines(&quoted.join("\n"), true, &mut inner);
+                blocks.push(Block::Quote(inner));
+                continue;
+            }
+        }
+
+        if let Some((level, content)) = heading_line(line) {
+            flush_paragraph(&mut paragraph, blocks);
+            blocks.push(Block::Heading {
+                level,
+                content: parse_inline(content),
+            });
+            i += 1;
+            continue;
+        }
+
+        if list_line(line).is_some() {
+            flush_paragraph(&mut paragraph, blocks);
+            let mut entries = Vec::new();
+            while let Some(entry) = lines.get(i).and_then(|l| list_line(l)) {
+                entries.push(entry);
+                i += 1;
+            }
+            blocks.push(build_list(&entries));
+            continue;
+        }
+
+        paragraph.push(line);
+        i += 1;
+    }
+    flush_paragraph(&mut paragraph, blocks);
+}
+
+fn flush_paragraph(paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>) {
+    if paragraph.is_empty() {
+        return;
+    }
+    let inlines = parse_inline(&paragraph.join("\n"));
+    paragraph.clear();
+    if !inlines.is_empty() {
+        blocks.push(Block::Paragraph(inlines));
+    }
+}
+
+fn quote_line(line: &str) -> Option<&str> {
+    if line == ">" {
+        Some("")
+    } else {
+        line.strip_prefix("> ")
+    }
+}
+
+fn heading_line(line: &str) -> Option<(u8, &str)> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=3).contains(&hashes) {
+        return None;
+    }
+    let content = line[hashes..].strip_prefix(' ')?;
+    if content.trim().is_empty() {
+        return None;
+    }
+    Some((hashes as u8, content))
+}
+
+fn list_line(line: &str) -> Option<ListEntry<'_>> {
+    let trimmed = line.trim_start_matches(' ');
+    let indent = line.len() - trimmed.len();
+    if let Some(text) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+    {
+        return Some(ListEntry {
+            indent,
+            number: None,
+            text,
+        });
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let text = trimmed[digits..].strip_prefix(". ")?;
+    Some(ListEntry {
+        indent,
+        number: trimmed[..digits].parse().ok(),
+        text,
+    })
+}
+
+/// Entries indented further than the first one become children of the entry
+/// above them.
+fn build_list(entries: &[ListEntry]) -> Block {
+    let level = entries[0].indent;
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < entries.len() {
+        let mut end = i + 1;
+        while end < entries.len() && entries[end].indent > level {
+            end += 1;
+        }
+        let children = if end > i + 1 {
+            vec![build_list(&entries[i + 1..end])]
+        } else {
+            Vec::new()
+        };
+        items.push(ListItem {
+            content: parse_inline(entries[i].text),
+            children,
+        });
+        i = end;
+    }
+    Block::List {
+        start: entries[0].number,
+        items,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Delimiter {
+    BoldItalic,
+    Bold,
+    Underline,
+    Italic(char),
+    Strikethrough,
+    Spoiler,
+    LinkText,
+}
+
+impl Delimiter {
+    fn marker(self) -> &'static str {
+        match self {
+            Self::BoldItalic => "***",
+            Self::Bold => "**",
+            Self::Underline => "__",
+            Self::Italic('_') => "_",
+            Self::Italic(_) => "*",
+            Self::Strikethrough => "~~",
+            Self::Spoiler => "||",
+            Self::LinkText => "]",
+        }
+    }
+
+    fn len(self) -> usize {
+        self.marker().len()
+    }
+}
+
+const ESCAPABLE: &str = "\\*_~`|>#-[]()<:@!&";
+
+/// Deepest nesting of emphasis before further delimiters are taken literally.
+const MAX_DEPTH: usize = 32;
+
+pub fn parse_inline(input: &str) -> Vec<Inline> {
+    let chars: Vec<char> = input.chars().collect();
+    let end = chars.len();
+    let mut parser = InlineParser {
+        chars,
+        no_closer: HashMap::new(),
+        no_code_closer: HashMap::new(),
+    };
+    parser.parse_range(0, end, 0)
+}
+
+/// Like Discord's own rules, a delimiter is closed by the first matching
+/// closer in the raw text, and only the text between them is parsed further.
+/// This keeps parsing roughly linear even on adversarial input.
+struct InlineParser {
+    chars: Vec<char>,
+    /// For a delimiter and the end of the range being scanned, the earliest
+    /// position a scan for its closer started from and failed. Any later scan
+    /// towards the same end must fail too.
+    no_closer: HashMap<(Delimiter, usize), usize>,
+    /// The same for code spans, keyed by the number of backticks.
+    no_code_closer: HashMap<(usize, usize), usize>,
+}
+
+impl InlineParser {
+    fn starts_with(&self, at: usize, end: usize, s: &str) -> bool {
+        (at..)
+            .zip(s.chars())
+            .all(|(i, c)| i < end && self.chars[i] == c)
+    }
+
+    fn char_before(&self, at: usize) -> Option<char> {
+        at.checked_sub(1).map(|i| self.chars[i])
+    }
+
+    fn char_at(&self, at: usize, end: usize) -> Option<char> {
+        if at < end {
+            Some(self.chars[at])
+        } else {
+            None
+        }
+    }
+
+    fn parse_range(&mut self, start: usize, end: usize, depth: usize) -> Vec<Inline> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        let mut pos = start;
+        while pos < end {
+            let c = self.chars[pos];
+
+            if c == '\\' {
+                if let Some(next) = self
+                    .char_at(pos + 1, end)
+                    .filter(|n| ESCAPABLE.contains(*n))
+                {
+                    text.push(next);
+                    pos += 2;
+                    continue;
+                }
+            }
+            if c == '\n' {
+                flush_text(&mut text, &mut nodes);
+                nodes.push(Inline::LineBreak);
+                pos += 1;
+                continue;
+            }
+            if c == '`' {
+                if let Some((code, next)) = self.code_span(pos, end) {
+                    flush_text(&mut text, &mut nodes);
+                    nodes.push(Inline::Code(code));
+                    pos = next;
+                    continue;
+                }
+            }
+            if c == '<' {
+                if let Some((token, next)) = self.angle_token(pos, end) {
+                    flush_text(&mut text, &mut nodes);
+                    nodes.push(token);
+                    pos = next;
+                    continue;
+                }
+            }
+            if c == '@' && !self.char_before(pos).is_some_and(char::is_alphanumeric) {
+                if let Some(name) = ["everyone", "here"]
+                    .into_iter()
+                    .find(|name| self.starts_with(pos + 1, end, name))
+                {
+                    flush_text(&mut text, &mut nodes);
+                    nodes.push(Inline::MassMention(name.to_string()));
+                    pos += 1 + name.len();
+                    continue;
+                }
+            }
+            if let Some((url, next)) = self.url(pos, end) {
+                flush_text(&mut text, &mut nodes);
+                nodes.push(Inline::Url(url));
+                pos = next;
+                continue;
+            }
+            if depth < MAX_DEPTH {
+                if c == '[' {
+                    if let Some((link, next)) = self.masked_link(pos, end, depth) {
+                        flush_text(&mut text, &mut nodes);
+                        nodes.push(link);
+                        pos = next;
+                        continue;
+                    }
+                }
+                if let Some(delimiter) = self.opener(pos, end) {
+                    let len = delimiter.len();
+                    if let Some(close) = self.find_closer(delimiter, pos + len, end) {
+                        let inner = self.parse_range(pos + len, close, depth + 1);
+                        if !inner.is_empty() {
+                            flush_text(&mut text, &mut nodes);
+                            nodes.push(wrap(delimiter, inner));
+                            pos = close + len;
+                            continue;
+                        }
+                    }
+                    // Not markup after all: keep the delimiter as text.
+                    text.extend(&self.chars[pos..pos + len]);
+                    pos += len;
+                    continue;
+                }
+            }
+            text.push(c);
+            pos += 1;
+        }
+        flush_text(&mut text, &mut nodes);
+        nodes
+    }
+
+    fn opener(&self, at: usize, end: usize) -> Option<Delimiter> {
+        let space_at = |i: usize| self.char_at(i, end).is_none_or(char::is_whitespace);
+        if self.starts_with(at, end, "***") && !space_at(at + 3) {
+            Some(Delimiter::BoldItalic)
+        } else if self.starts_with(at, end, "**") && !space_at(at + 2) {
+            Some(Delimiter::Bold)
+        } else if self.starts_with(at, end, "__") {
+            Some(Delimiter::Underline)
+        } else if self.starts_with(at, end, "~~") {
+            Some(Delimiter::Strikethrough)
+        } else if self.starts_with(at, end, "||") {
+            Some(Delimiter::Spoiler)
+        } else if self.starts_with(at, end, "*") && !space_at(at + 1) {
+            Some(Delimiter::Italic('*'))
+        } else if self.starts_with(at, end, "_")
+            && !self.char_before(at).is_some_and(char::is_alphanumeric)
+        {
+            Some(Delimiter::Italic('_'))
+        } else {
+            None
+        }
+    }
+
+    fn is_closer(&self, delimiter: Delimiter, at: usize, end: usize) -> bool {
+        if !self.starts_with(at, end, delimiter.marker()) {
+            return false;
+        }
+        let after = self.char_at(at + delimiter.len(), end);
+        match delimiter {
+            // `snake_case_names` should not turn italic.
+            Delimiter::Italic('_') => !after.is_some_and(char::is_alphanumeric),
+            Delimiter::Italic(_) => {
+                !self.char_before(at).is_some_and(char::is_whitespace) && after != Some('*')
+            }
+            Delimiter::Bold => after != Some('*'),
+            Delimiter::Underline => after != Some('_'),
+            _ => true,
+        }
+    }
+
+    /// The position of the closer for a delimiter whose content starts at
+    /// `from`. Content must be at least one character long.
+    fn find_closer(&mut self, delimiter: Delimiter, from: usize, end: usize) -> Option<usize> {
+        if let Some(&failed) = self.no_closer.get(&(delimiter, end)) {
+            if from >= failed {
+                return None;
+            }
+        }
+        let mut i = from;
+        while i < end {
+            if self.chars[i] == '\\' {
+                i += 2;
+                continue;
+            }
+            // `*a **b** c*`: doubled stars inside italics belong to bold.
+            if delimiter == Delimiter::Italic('*') && self.starts_with(i, end, "**") {
+                i += 2;
+                continue;
+            }
+            if i > from && self.is_closer(delimiter, i, end) {
+                return Some(i);
+            }
+            i += 1;
+        }
+        let failed = self.no_closer.entry((delimiter, end)).or_insert(from);
+        *failed = (*failed).min(from);
+        None
+    }
+
+    /// `` `code` `` or ``` ``code with ` inside`` ```. Returns the code and
+    /// the position after the span.
+    fn code_span(&mut self, at: usize, end: usize) -> Option<(String, usize)> {
+        let ticks = if self.starts_with(at, end, "``") {
+            2
+        } else {
+            1
+        };
+        let from = at + ticks;
+        if let Some(&failed) = self.no_code_closer.get(&(ticks, end)) {
+            if from >= failed {
+                return None;
+            }
+        }
+        let is_fence = |i: usize| {
+            (i..i + ticks).all(|j| self.char_at(j, end) == Some('`'))
+                && self.char_at(i + ticks, end) != Some('`')
+                && self.char_before(i) != Some('`')
+        };
+        let mut i = from + 1;
+        while i < end {
+            if is_fence(i) {
+                let code = self.chars[from..i].iter().collect();
+                return Some((code, i + ticks));
+            }
+            i += 1;
+        }
+        let failed = self.no_code_closer.entry((ticks, end)).or_insert(from);
+        *failed = (*failed).min(from);
+        None
+    }
+
+    fn url(&self, at: usize, end: usize) -> Option<(String, usize)> {
+        let is_scheme =
+            |i: usize| self.starts_with(i, end, "http://") || self.starts_with(i, end, "https://");
+        if self.chars[at] == '<' && is_scheme(at + 1) {
+            let mut i = at + 1;
+            while i < end && self.chars[i] != '>' {
+                if self.chars[i].is_whitespace() {
+                    return None;
+                }
+                i += 1;
+            }
+            if i == end {
+                return None;
+            }
+            return Some((self.chars[at + 1..i].iter().collect(), i + 1));
+        }
+        if !is_scheme(at) || self.char_before(at).is_some_and(char::is_alphanumeric) {
+            return None;
+        }
+        let mut i = at;
+        while i < end && !self.chars[i].is_whitespace() {
+            i += 1;
+        }
+        while i > at && ".,:;!?\"')]".contains(self.chars[i - 1]) {
+            i -= 1;
+        }
+        let url: String = self.chars[at..i].iter().collect();
+        if url.ends_with("://") {
+            return None;
+        }
+        Some((url, i))
+    }
+
+    /// Mentions, custom emoji and timestamps, which all look like `<...>`.
+    /// Returns the token and the position after it.
+    fn angle_token(&self, at: usize, end: usize) -> Option<(Inline, usize)> {
+        let close = (at + 1..end.min(at + 128)).find(|&i| self.chars[i] == '>')?;
+        let body: String = self.chars[at + 1..close].iter().collect();
+        let next = close + 1;
+        let id = |s: &str| s.parse::<Snowflake>().ok();
+        let token = if let Some(rest) = body.strip_prefix("@&") {
+            Inline::RoleMention(id(rest)?)
+        } else if let Some(rest) = body.strip_prefix("@!") {
+            Inline::UserMention(id(rest)?)
+        } else if let Some(rest) = body.strip_prefix('@') {
+            Inline::UserMention(id(rest)?)
+        } else if let Some(rest) = body.strip_prefix('#') {
+            Inline::ChannelMention(id(rest)?)
+        } else if let Some(rest) = body.strip_prefix("t:") {
+            let (unix, style) = match rest.split_once(':') {
+                Some((unix, style)) => {
+                    let mut chars = style.chars();
+                    let style = chars.next().filter(|c| "tTdDfFR".contains(*c))?;
+                    if chars.next().is_some() {
+                        return None;
+                    }
+                    (unix, Some(style))
+                }
+                None => (rest, None),
+            };
+            Inline::Timestamp {
+                unix: unix.parse().ok()?,
+                style,
+            }
+        } else {
+            let (animated, rest) = match body.strip_prefix("a:") {
+                Some(rest) => (true, rest),
+                None => (false, body.strip_prefix(':')?),
+            };
+            let (name, emoji_id) = rest.split_once(':')?;
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return None;
+            }
+            Inline::Emoji {
+                name: name.to_string(),
+                id: id(emoji_id)?,
+                animated,
+            }
+        };
+        Some((token, next))
+    }
+
+    /// `[text](https://url)`. Returns the link and the position after it.
+    fn masked_link(&mut self, at: usize, end: usize, depth: usize) -> Option<(Inline, usize)> {
+        let close = self.find_closer(Delimiter::LinkText, at + 1, end)?;
+        if !self.starts_with(close, end, "](") {
+            return None;
+        }
+        let url_start = close + 2;
+        let url_end = (url_start..end).find(|&i| self.chars[i] == ')')?;
+        let url: String = self.chars[url_start..url_end].iter().collect();
+        if !(url.starts_with("http://") || url.starts_with("https://"))
+            || url.contains(char::is_whitespace)
+        {
+            return None;
+        }
+        let text = self.parse_range(at + 1, close, depth + 1);
+        if text.is_empty() {
+            return None;
+        }
+        Some((Inline::Link { text, url }, url_end + 1))
+    }
+}
+
+fn wrap(delimiter: Delimiter, content: Vec<Inline>) -> Inline {
+    match delimiter {
+        Delimiter::BoldItalic => Inline::Bold(vec![Inline::Italic(content)]),
+        Delimiter::Bold => Inline::Bold(content),
+        Delimiter::Underline => Inline::Underline(content),
+        Delimiter::Italic(_) => Inline::Italic(content),
+        Delimiter::Strikethrough => Inline::Strikethrough(content),
+        Delimiter::Spoiler => Inline::Spoiler(content),
+        Delimiter::LinkText => unreachable!("link text is not an opener"),
+    }
+}
+
+fn flush_text(text: &mut String, nodes: &mut Vec<Inline>) {
+    if !text.is_empty() {
+        nodes.push(Inline::Text(std::mem::take(text)));
+    }
+}
+
+/// The text of `inlines` with all markup removed.
+pub fn plain_text(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) | Inline::Code(text) | Inline::Url(text) => out.push_str(text),
+            Inline::Bold(inner)
+            | Inline::Italic(inner)
+            | Inline::Underline(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Spoiler(inner)
+            | Inline::Link { text: inner, .. } => out.push_str(&plain_text(inner)),
+            Inline::UserMention(id) => out.push_str(&format!("<@{}>", id)),
+            Inline::ChannelMention(id) => out.push_str(&format!("<#{}>", id)),
+            Inline::RoleMention(id) => out.push_str(&format!("<@&{}>", id)),
+            Inline::MassMention(name) => {
+                out.push('@');
+                out.push_str(name);
+            }
+            Inline::Emoji { name, .. } => out.push_str(&format!(":{}:", name)),
+            Inline::Timestamp { unix, .. } => out.push_str(&unix.to_string()),
+            Inline::LineBreak => out.push('\n'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// A compact rendering of the tree so golden cases fit on one line.
+    fn show_inlines(inlines: &[Inline]) -> String {
+        inlines
+            .iter()
+            .map(|inline| match inline {
+                Inline::Text(t) => format!("{:?}", t),
+                Inline::Bold(i) => format!("b({})", show_inlines(i)),
+                Inline::Italic(i) => format!("i({})", show_inlines(i)),
+                Inline::Underline(i) => format!("u({})", show_inlines(i)),
+                Inline::Strikethrough(i) => format!("s({})", show_inlines(i)),
+                Inline::Spoiler(i) => format!("spoiler({})", show_inlines(i)),
+                Inline::Code(c) => format!("code({:?})", c),
+                Inline::Link { text, url } => format!("link({}, {})", show_inlines(text), url),
+                Inline::Url(u) => format!("url({})", u),
+                Inline::UserMention(id) => format!("user({})", id),
+                Inline::ChannelMention(id) => format!("channel({})", id),
+                Inline::RoleMention(id) => format!("role({})", id),
+                Inline::MassMention(name) => format!("mass({})", name),
+                Inline::Emoji { name, id, animated } => {
+                    format!("emoji({}, {}, {})", name, id, animated)
+                }
+                Inline::Timestamp { unix, style } => format!("time({}, {:?})", unix, style),
+                Inline::LineBreak => "br".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn show_blocks(blocks: &[Block]) -> String {
+        blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph(i) => format!("p[{}]", show_inlines(i)),
+                Block::Heading { level, content } => {
+                    format!("h{}[{}]", level, show_inlines(content))
+                }
+                Block::Quote(b) => format!("quote[{}]", show_blocks(b)),
+                Block::CodeBlock { language, code } => {
+                    format!("codeblock[{:?}, {:?}]", language, code)
+                }
+                Block::List { start, items } => format!(
+                    "list[{:?}; {}]",
+                    start,
+                    items
+                        .iter()
+                        .map(|item| {
+                            let mut s = show_inlines(&item.content);
+                            if !item.children.is_empty() {
+                                s = format!("{} > {}", s, show_blocks(&item.children));
+                            }
+                            s
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn golden() {
+        let cases = [
+            ("hello", r#"p["hello"]"#),
+            ("**bold**", r#"p[b("bold")]"#),
+            ("*it* and _it_", r#"p[i("it") " and " i("it")]"#),
+            ("***both***", r#"p[b(i("both"))]"#),
+            ("__under__ ~~gone~~", r#"p[u("under") " " s("gone")]"#),
+            ("||secret||", r#"p[spoiler("secret")]"#),
+            ("*a **b** c*", r#"p[i("a " b("b") " c")]"#),
+            ("**a *b* c**", r#"p[b("a " i("b") " c")]"#),
+            ("__*nested*__", r#"p[u(i("nested"))]"#),
+            ("snake_case_name", r#"p["snake_case_name"]"#),
+            ("2 * 3 * 4", r#"p["2 * 3 * 4"]"#),
+            ("**unclosed", r#"p["**unclosed"]"#),
+            ("****", r#"p["****"]"#),
+            (r"\*not italic\*", r#"p["*not italic*"]"#),
+            ("`**code**`", r#"p[code("**code**")]"#),
+            ("``a ` b``", r#"p[code("a ` b")]"#),
+            ("`unclosed", r#"p["`unclosed"]"#),
+            (
+                "see https://example.com/a_b_c.",
+                r#"p["see " url(https://example.com/a_b_c) "."]"#,
+            ),
+            ("<https://example.com>", r#"p[url(https://example.com)]"#),
+            (
+                "[**docs**](https://docs.rs)",
+                r#"p[link(b("docs"), https://docs.rs)]"#,
+            ),
+            ("[not a link](ftp://x)", r#"p["[not a link](ftp://x)"]"#),
+            ("line one\nline two", r#"p["line one" br "line two"]"#),
+            ("# Title", r#"h1["Title"]"#),
+            ("#hashtag", "p[\"#hashtag\"]"),
+            ("#### too deep", "p[\"#### too deep\"]"),
+            (
+                "> quoted\n> more\nafter",
+                r#"quote[p["quoted" br "more"]] p["after"]"#,
+            ),
+            (">>> all\nof this", r#"quote[p["all" br "of this"]]"#),
+            ("> > not nested", r#"quote[p["> not nested"]]"#),
+            (
+                "```rust\nfn main() {}\n```",
+                r#"codeblock[Some("rust"), "fn main() {}"]"#,
+            ),
+            ("```not a tag\nx```", r#"codeblock[None, "not a tag\nx"]"#),
+            (
+                "before ```**x**``` after",
+                r#"p["before "] codeblock[None, "**x**"] p[" after"]"#,
+            ),
+            ("``````", r#"p["``````"]"#),
+            (
+                "- one\n- two\n  - nested\n- three",
+                r#"list[None; "one" | "two" > list[None; "nested"] | "three"]"#,
+            ),
+            ("3. c\n4. d", r#"list[Some(3); "c" | "d"]"#),
+            ("-not a list", r#"p["-not a list"]"#),
+            (
+                "hi <@123> and <@!456>",
+                r#"p["hi " user(123) " and " user(456)]"#,
+            ),
+            ("<#1> <@&2>", r#"p[channel(1) " " role(2)]"#),
+            ("<@abc> <#>", r#"p["<@abc> <#>"]"#),
+            (
+                "@everyone @here me@here",
+                r#"p[mass(everyone) " " mass(here) " me@here"]"#,
+            ),
+            (
+                "<:blob:10> <a:dance:11>",
+                r#"p[emoji(blob, 10, false) " " emoji(dance, 11, true)]"#,
+            ),
+            (
+                "<t:1618953630> <t:1618953630:R> <t:1:x>",
+                r#"p[time(1618953630, None) " " time(1618953630, Some('R')) " <t:1:x>"]"#,
+            ),
+            ("`<@123>`", r#"p[code("<@123>")]"#),
+            (r"\<@123>", r#"p["<@123>"]"#),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(show_blocks(&parse(input)), expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn plain_text_without_markup_round_trips() {
+        let input = "just some words, nothing special";
+        assert_eq!(
+            parse(input),
+            vec![Block::Paragraph(vec![Inline::Text(input.to_string())])]
+        );
+    }
+
+    /// Random inputs built from markup characters must never panic, and
+    /// stripping the markup must never invent text that was not there.
+    #[test]
+    fn fuzz() {
+        const ALPHABET: &[&str] = &[
+            "*",
+            "**",
+            "_",
+            "__",
+            "~~",
+            "||",
+            "`",
+            "```",
+            "\\",
+            ">",
+            "> ",
+            ">>> ",
+            "#",
+            "# ",
+            "- ",
+            "1. ",
+            "[",
+            "]",
+            "(",
+            ")",
+            "https://a.b",
+            "<",
+            ">",
+            "\n",
+            " ",
+            "a",
+            "b",
+            "é",
+            "🦀",
+        ];
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for _ in 0..20_000 {
+            let len = rng.gen_range(0..40);
+            let input: String = (0..len)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())])
+                .collect();
+            let blocks = parse(&input);
+            for block in &blocks {
+                if let Block::Paragraph(inlines) = block {
+                    let text = plain_text(inlines);
+                    let allowed = |c: char| input.contains(c);
+                    assert!(text.chars().all(allowed), "input: {:?}", input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pathological_nesting_terminates() {
+        // Discord caps messages at 4000 characters.
+        for input in [
+            "*_~~||".repeat(666),
+            "[".repeat(4_000),
+            "`".repeat(4_000),
+            "**".repeat(2_000),
+            "_a".repeat(2_000),
+            "> ".repeat(2_000),
+            "https://a ".repeat(400),
+        ] {
+            parse(&input);
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use serde::Deserialize;
+
+use super::gateway;
+use super::permissions::Role;
+use super::presence::{PresenceUpdate, UserPresence};
+use super::snowflake::Snowflake;
+use super::types::Status;
+
+/// Rows the gateway sends per op 14 range.
+pub const RANGE_SIZE: usize = 100;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MemberUser {
+    pub id: Snowflake,
+    pub username: String,
+    pub discriminator: String,
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub bot: bool,
+}
+
+/// A guild member as the member list shows them.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Member {
+    pub user: MemberUser,
+    pub nick: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<Snowflake>,
+    /// Sent with lazy list items; filled in from the chunk's presences for
+    /// op 8 responses.
+    #[serde(default)]
+    pub presence: Option<UserPresence>,
+}
+
+impl Member {
+    pub fn display_name(&self) -> &str {
+        self.nick.as_deref().unwrap_or(&self.user.username)
+    }
+
+    pub fn status(&self) -> Status {
+        self.presence.as_ref().map(|p| p.status).unwrap_or_default()
+    }
+}
+
+/// A section of the list: a hoisted role's id, or "online" or "offline".
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Group {
+    pub id: String,
+    #[serde(default)]
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListItem {
+    Group(Group),
+    Member(Box<Member>),
+}
+
+/// GUILD_MEMBER_LIST_UPDATE, the gateway's answer to op 14 and its updates
+/// afterwards.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MemberListUpdate {
+    pub guild_id: Snowflake,
+    #[serde(default)]
+    pub member_count: u64,
+    #[serde(default)]
+    pub online_count: u64,
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    #[serde(default)]
+    pub ops: Vec<ListOp>,
+}
+
+impl MemberListUpdate {
+    /// The members the update carries, to pick their presences out of.
+    pub fn members(&self) -> impl Iterator<Item = &Member> {
+        self.ops
+            .iter()
+            .flat_map(|op| match op {
+                ListOp::Sync { items, .. } => items.iter().collect(),
+                ListOp::Insert { item, .. } | ListOp::Update { item, .. } => vec![item],
+                ListOp::Delete { .. } | ListOp::Invalidate { .. } => Vec::new(),
+            })
+            .filter_map(|item| match item {
+                ListItem::Member(member) => Some(member.as_ref()),
+                ListItem::Group(_) => None,
+            })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "UPPERCASE")]
+pub enum ListOp {
+    Sync {
+        range: [usize; 2],
+        #[serde(default)]
+        items: Vec<ListItem>,
+    },
+    Insert {
+        index: usize,
+        item: ListItem,
+    },
+    Update {
+        index: usize,
+        item: ListItem,
+    },
+    Delete {
+        index: usize,
+    },
+    Invalidate {
+        range: [usize; 2],
+    },
+}
+
+/// GUILD_MEMBERS_CHUNK, the answer to op 8.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MembersChunk {
+    pub guild_id: Snowflake,
+    #[serde(default)]
+    pub members: Vec<Member>,
+    #[serde(default)]
+    pub presences: Vec<PresenceUpdate>,
+}
+
+impl MembersChunk {
+    /// The chunk's members with their presences attached.
+    pub fn into_members(self) -> Vec<Member> {
+        let presences = self.presences;
+        self.members
+            .into_iter()
+            .map(|mut member| {
+                if let Some(update) = presences
+                    .iter()
+                    .find(|p| p.user_id() == Some(member.user.id))
+                {
+                    member.presence = Some(update.presence.clone());
+                }
+                member
+            })
+            .collect()
+    }
+}
+
+/// A guild's member sidebar: group headers and members in display order.
+/// Rows the gateway has not sent yet are `None`.
+#[derive(Clone, Debug, Default)]
+pub struct MemberList {
+    pub guild_id: Option<Snowflake>,
+    pub rows: Vec<Option<ListItem>>,
+    pub member_count: u64,
+    pub online_count: u64,
+}
+
+impl MemberList {
+    pub fn new(guild_id: Snowflake) -> Self {
+        Self {
+            guild_id: Some(guild_id),
+            ..Self::default()
+        }
+    }
+
+    pub fn apply(&mut self, update: MemberListUpdate) {
+        self.member_count = update.member_count;
+        self.online_count = update.online_count;
+        if !update.groups.is_empty() {
+            // One header per group, then its members.
+            let total = update.groups.iter().map(|g| g.count as usize + 1).sum();
+            self.rows.resize(total, None);
+        }
+        for op in update.ops {
+            match op {
+                ListOp::Sync { range, items } => {
+                    for (offset, item) in items.into_iter().enumerate() {
+                        let index = range[0] + offset;
+                        if index >= self.rows.len() {
+                            self.rows.resize(index + 1, None);
+                        }
+                        self.rows[index] = Some(item);
+                    }
+                }
+                ListOp::Insert { index, item } => {
+                    let index = index.min(self.rows.len());
+                    self.rows.insert(index, Some(item));
+                }
+                ListOp::Update { index, item } => {
+                    if let Some(row) = self.rows.get_mut(index) {
+                        *row = Some(item);
+                    }
+                }
+                ListOp::Delete { index } => {
+                    if index < self.rows.len() {
+                        self.rows.remove(index);
+                    }
+                }
+                ListOp::Invalidate { range } => {
+                    let end = (range[1] + 1).min(self.rows.len());
+                    for row in self.rows.iter_mut().take(end).skip(range[0]) {
+                        *row = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Lays out a complete member list the way Discord groups it: online
+    /// members under their highest hoisted role, the other online members,
+    /// then everyone offline. Used for op 8, which sends raw members.
+    pub fn from_members(guild_id: Snowflake, members: &[Member], roles: &[Role]) -> Self {
+        let mut hoisted: Vec<&Role> = roles.iter().filter(|r| r.hoist).collect();
+        hoisted.sort_by_key(|r| std::cmp::Reverse(r.position));
+        let mut groups: Vec<(String, Vec<&Member>)> = hoisted
+            .iter()
+            .map(|r| (r.id.to_string(), Vec::new()))
+            .collect();
+        groups.push(("online".to_string(), Vec::new()));
+        groups.push(("offline".to_string(), Vec::new()));
+        let offline = groups.len() - 1;
+        let online = offline - 1;
+        let mut online_count = 0;
+        for member in members {
+            let group = if member.status().is_online() {
+                online_count += 1;
+                hoisted
+                    .iter()
+                    .position(|r| member.roles.contains(&r.id))
+                    .unwrap_or(online)
+            } else {
+                offline
+            };
+            groups[group].1.push(member);
+        }
+        let mut rows = Vec::new();
+        for (id, mut members) in groups {
+            if members.is_empty() {
+                continue;
+            }
+            members.sort_by_key(|m| m.display_name().to_lowercase());
+            rows.push(Some(ListItem::Group(Group {
+                id,
+                count: members.len() as u64,
+            })));
+            rows.extend(
+                members
+                    .into_iter()
+                    .map(|m| Some(ListItem::Member(Box::new(m.clone())))),
+            );
+        }
+        Self {
+            guild_id: Some(guild_id),
+            rows,
+            member_count: members.len() as u64,
+            online_count,
+        }
+    }
+}
+
+/// Asks for every member of the guild (op 8). Only bots may do this, and
+/// only with the GUILD_MEMBERS and GUILD_PRESENCES intents, which the gateway
+/// identifies with for bot tokens. The answer comes as GUILD_MEMBERS_CHUNK
+/// events.
+pub fn request_guild_members(guild_id: Snowflake) {
+    gateway::send_command(
+        8,
+        serde_json::json!({
+            "guild_id": guild_id,
+            "query": "",
+            "limit": 0,
+            "presences": true,
+        }),
+    );
+}
+
+/// Subscribes to the member list as seen from `channel_id` (op 14), for the
+/// first block of rows and the block containing `visible_row`. The answer
+/// comes as GUILD_MEMBER_LIST_UPDATE events.
+pub fn subscribe(guild_id: Snowflake, channel_id: Snowflake, visible_row: usize) {
+    let block = visible_row / RANGE_SIZE * RANGE_SIZE;
+    let mut ranges = vec![[0, RANGE_SIZE - 1]];
+    if block > 0 {
+        ranges.push([block, block + RANGE_SIZE - 1]);
+    }
+    gateway::send_command(
+        14,
+        serde_json::json!({
+            "guild_id": guild_id,
+            "typing": true,
+            "activities": true,
+            "threads": true,
+            "channels": { channel_id.to_string(): ranges },
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: u64, name: &str, status: &str, roles: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "user": {"id": id.to_string(), "username": name, "discriminator": "0", "avatar": null},
+            "nick": null,
+            "roles": roles,
+            "presence": {"status": status}
+        })
+    }
+
+    #[test]
+    fn applies_list_ops() {
+        let update: MemberListUpdate = serde_json::from_value(serde_json::json!({
+            "guild_id": "1",
+            "member_count": 3,
+            "online_count": 2,
+            "groups": [{"id": "online", "count": 2}, {"id": "offline", "count": 1}],
+            "ops": [{"op": "SYNC", "range": [0, 99], "items": [
+                {"group": {"id": "online", "count": 2}},
+                {"member": member(10, "alice", "online", &[])},
+                {"member": member(11, "bob", "idle", &[])},
+                {"group": {"id": "offline", "count": 1}},
+                {"member": member(12, "carol", "offline", &[])}
+            ]}]
+        }))
+        .unwrap();
+        let mut list = MemberList::new(Snowflake(1));
+        list.apply(update);
+        assert_eq!(list.rows.len(), 5);
+
+        let update: MemberListUpdate = serde_json::from_value(serde_json::json!({
+            "guild_id": "1",
+            "ops": [
+                {"op": "DELETE", "index": 2},
+                {"op": "INVALIDATE", "range": [3, 3]}
+            ]
+        }))
+        .unwrap();
+        list.apply(update);
+        assert_eq!(list.rows.len(), 4);
+        assert!(list.rows[3].is_none());
+        match &list.rows[1] {
+            Some(ListItem::Member(member)) => assert_eq!(member.display_name(), "alice"),
+            other => panic!("expected alice, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn groups_members_by_hoisted_role_and_status() {
+        let roles: Vec<Role> = serde_json::from_value(serde_json::json!([{
+            "id": "5", "name": "Mods", "color": 0, "hoist": true, "position": 1,
+            "permissions": "0", "managed": false, "mentionable": false
+        }]))
+        .unwrap();
+        let members: Vec<Member> = serde_json::from_value(serde_json::json!([
+            member(10, "zed", "online", &[]),
+            member(11, "amy", "dnd", &["5"]),
+            member(12, "bea", "offline", &["5"])
+        ]))
+        .unwrap();
+        let list = MemberList::from_members(Snowflake(1), &members, &roles);
+        let ids: Vec<String> = list
+            .rows
+            .iter()
+            .map(|row| match row {
+                Some(ListItem::Group(group)) => group.id.clone(),
+                Some(ListItem::Member(member)) => member.display_name().to_string(),
+                None => String::new(),
+            })
+            .collect();
+        assert_eq!(ids, ["5", "amy", "online", "zed", "offline", "bea"]);
+        assert_eq!(list.online_count, 2);
+    }
+}
@@ -1 +1,15 @@
+pub mod api;
+pub mod cdn;
+pub mod commands;
+pub mod forum;
 pub mod gateway;
+pub mod interactions;
+pub mod markdown;
+pub mod members;
+pub mod permissions;
+pub mod pins;
+pub mod presence;
+pub mod read_state;
+pub mod snowflake;
+pub mod threads;
+pub mod types;
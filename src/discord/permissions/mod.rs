@@ -0,0 +1,340 @@
+use bitflags::bitflags;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use super::snowflake::Snowflake;
+
+bitflags! {
+    #[derive(Default)]
+    pub struct Permissions: u64 {
+        const CREATE_INSTANT_INVITE = 1 << 0;
+        const KICK_MEMBERS = 1 << 1;
+        const BAN_MEMBERS = 1 << 2;
+        const ADMINISTRATOR = 1 << 3;
+        const MANAGE_CHANNELS = 1 << 4;
+        const MANAGE_GUILD = 1 << 5;
+        const ADD_REACTIONS = 1 << 6;
+        const VIEW_AUDIT_LOG = 1 << 7;
+        const PRIORITY_SPEAKER = 1 << 8;
+        const STREAM = 1 << 9;
+        const VIEW_CHANNEL = 1 << 10;
+        const SEND_MESSAGES = 1 << 11;
+        const SEND_TTS_MESSAGES = 1 << 12;
+        const MANAGE_MESSAGES = 1 << 13;
+        const EMBED_LINKS = 1 << 14;
+        const ATTACH_FILES = 1 << 15;
+        const READ_MESSAGE_HISTORY = 1 << 16;
+        const MENTION_EVERYONE = 1 << 17;
+        const USE_EXTERNAL_EMOJIS = 1 << 18;
+        const VIEW_GUILD_INSIGHTS = 1 << 19;
+        const CONNECT = 1 << 20;
+        const SPEAK = 1 << 21;
+        const MUTE_MEMBERS = 1 << 22;
+        const DEAFEN_MEMBERS = 1 << 23;
+        const MOVE_MEMBERS = 1 << 24;
+        const USE_VAD = 1 << 25;
+        const CHANGE_NICKNAME = 1 << 26;
+        const MANAGE_NICKNAMES = 1 << 27;
+        const MANAGE_ROLES = 1 << 28;
+        const MANAGE_WEBHOOKS = 1 << 29;
+        const MANAGE_EMOJIS_AND_STICKERS = 1 << 30;
+        const USE_APPLICATION_COMMANDS = 1 << 31;
+        const REQUEST_TO_SPEAK = 1 << 32;
+        const MANAGE_EVENTS = 1 << 33;
+        const MANAGE_THREADS = 1 << 34;
+        const CREATE_PUBLIC_THREADS = 1 << 35;
+        const CREATE_PRIVATE_THREADS = 1 << 36;
+        const USE_EXTERNAL_STICKERS = 1 << 37;
+        const SEND_MESSAGES_IN_THREADS = 1 << 38;
+        const USE_EMBEDDED_ACTIVITIES = 1 << 39;
+        const MODERATE_MEMBERS = 1 << 40;
+    }
+}
+
+// Older API versions send permissions as integers, newer ones as strings, so
+// accept either and always write the string form back out.
+impl Serialize for Permissions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.bits().to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PermissionsVisitor;
+
+        impl<'de> Visitor<'de> for PermissionsVisitor {
+            type Value = Permissions;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a permission bitset as an integer or string")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Permissions, E> {
+                Ok(Permissions::from_bits_truncate(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Permissions, E> {
+                Ok(Permissions::from_bits_truncate(v as u64))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Permissions, E> {
+                v.parse::<u64>()
+                    .map(Permissions::from_bits_truncate)
+                    .map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(PermissionsVisitor)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PermissionOverwrite {
+    pub allow: Permissions,
+    pub deny: Permissions,
+    pub id: Snowflake,
+    pub r#type: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Role {
+    pub id: Snowflake,
+    pub name: String,
+    pub color: u64,
+    pub hoist: bool,
+    pub position: i64,
+    pub permissions: Permissions,
+    pub managed: bool,
+    pub mentionable: bool,
+}
+
+/// Our permissions in a guild before channel overwrites are applied, along
+/// with what is needed to apply them.
+#[derive(Clone, Debug)]
+pub struct GuildPermissions {
+    pub guild_id: Snowflake,
+    pub user_id: Snowflake,
+    pub member_roles: Vec<Snowflake>,
+    pub base: Permissions,
+}
+
+impl GuildPermissions {
+    pub fn new(
+        guild_id: Snowflake,
+        user_id: Snowflake,
+        owner: bool,
+        roles: &[Role],
+        member_roles: Vec<Snowflake>,
+    ) -> Self {
+        let base = compute_base_permissions(guild_id, owner, roles, &member_roles);
+        Self {
+            guild_id,
+            user_id,
+            member_roles,
+            base,
+        }
+    }
+
+    pub fn for_channel(&self, overwrites: &[PermissionOverwrite]) -> Permissions {
+        compute_overwrites(
+            self.base,
+            self.guild_id,
+            self.user_id,
+            &self.member_roles,
+            overwrites,
+        )
+    }
+}
+
+/// Combines the @everyone role (which shares the guild's id) with every role
+/// the member holds. Owners and administrators get everything.
+pub fn compute_base_permissions(
+    guild_id: Snowflake,
+    owner: bool,
+    roles: &[Role],
+    member_roles: &[Snowflake],
+) -> Permissions {
+    if owner {
+        return Permissions::all();
+    }
+    let mut permissions = Permissions::empty();
+    for role in roles {
+        if role.id == guild_id || member_roles.contains(&role.id) {
+            permissions |= role.permissions;
+        }
+    }
+    if permissions.contains(Permissions::ADMINISTRATOR) {
+        return Permissions::all();
+    }
+    permissions
+}
+
+/// Applies a channel's overwrites in Discord's order: @everyone, then all of
+/// the member's roles at once, then the member itself.
+pub fn compute_overwrites(
+    base: Permissions,
+    guild_id: Snowflake,
+    user_id: Snowflake,
+    member_roles: &[Snowflake],
+    overwrites: &[PermissionOverwrite],
+) -> Permissions {
+    if base.contains(Permissions::ADMINISTRATOR) {
+        return Permissions::all();
+    }
+    let mut permissions = base;
+
+    if let Some(everyone) = overwrites.iter().find(|o| o.id == guild_id) {
+        permissions.remove(everyone.deny);
+        permissions.insert(everyone.allow);
+    }
+
+    let mut allow = Permissions::empty();
+    let mut deny = Permissions::empty();
+    for overwrite in overwrites {
+        if member_roles.contains(&overwrite.id) {
+            allow |= overwrite.allow;
+            deny |= overwrite.deny;
+        }
+    }
+    permissions.remove(deny);
+    permissions.insert(allow);
+
+    if let Some(member) = overwrites.iter().find(|o| o.id == user_id) {
+        permissions.remove(member.deny);
+        permissions.insert(member.allow);
+    }
+
+    // Permissions that are meaningless without the ones they depend on.
+    if !permissions.contains(Permissions::VIEW_CHANNEL) {
+        return Permissions::empty();
+    }
+    if !permissions.contains(Permissions::SEND_MESSAGES) {
+        permissions.remove(
+            Permissions::SEND_TTS_MESSAGES
+                | Permissions::MENTION_EVERYONE
+                | Permissions::EMBED_LINKS
+                | Permissions::ATTACH_FILES,
+        );
+    }
+    permissions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: Snowflake = Snowflake(1);
+    const USER: Snowflake = Snowflake(2);
+    const MODS: Snowflake = Snowflake(3);
+    const MUTED: Snowflake = Snowflake(4);
+
+    fn role(id: Snowflake, permissions: Permissions) -> Role {
+        Role {
+            id,
+            name: format!("role {}", id.0),
+            color: 0,
+            hoist: false,
+            position: 0,
+            permissions,
+            managed: false,
+            mentionable: false,
+        }
+    }
+
+    fn overwrite(id: Snowflake, allow: Permissions, deny: Permissions) -> PermissionOverwrite {
+        PermissionOverwrite {
+            allow,
+            deny,
+            id,
+            r#type: if id == USER { "member" } else { "role" }.to_string(),
+        }
+    }
+
+    fn text() -> Permissions {
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES
+    }
+
+    #[test]
+    fn base_permissions_combine_everyone_and_member_roles() {
+        let roles = [
+            role(GUILD, text()),
+            role(MODS, Permissions::MANAGE_MESSAGES),
+            role(MUTED, Permissions::KICK_MEMBERS),
+        ];
+        let base = compute_base_permissions(GUILD, false, &roles, &[MODS]);
+        assert_eq!(base, text() | Permissions::MANAGE_MESSAGES);
+    }
+
+    #[test]
+    fn owners_and_administrators_get_everything() {
+        let roles = [role(GUILD, Permissions::empty())];
+        assert_eq!(
+            compute_base_permissions(GUILD, true, &roles, &[]),
+            Permissions::all()
+        );
+
+        let roles = [
+            role(GUILD, Permissions::empty()),
+            role(MODS, Permissions::ADMINISTRATOR),
+        ];
+        let base = compute_base_permissions(GUILD, false, &roles, &[MODS]);
+        assert_eq!(base, Permissions::all());
+        // Overwrites cannot take anything away from an administrator.
+        let overwrites = [overwrite(USER, Permissions::empty(), Permissions::all())];
+        assert_eq!(
+            compute_overwrites(base, GUILD, USER, &[MODS], &overwrites),
+            Permissions::all()
+        );
+    }
+
+    #[test]
+    fn overwrites_apply_everyone_then_roles_then_member() {
+        // @everyone loses sending, a role gives it back, and the member
+        // overwrite takes attachments away again.
+        let overwrites = [
+            overwrite(USER, Permissions::empty(), Permissions::ATTACH_FILES),
+            overwrite(GUILD, Permissions::empty(), Permissions::SEND_MESSAGES),
+            overwrite(MODS, Permissions::SEND_MESSAGES, Permissions::empty()),
+        ];
+        let permissions = compute_overwrites(text(), GUILD, USER, &[MODS], &overwrites);
+        assert_eq!(
+            permissions,
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES
+        );
+
+        // Role overwrites are merged before applying, so an allow on one role
+        // beats a deny on another.
+        let overwrites = [
+            overwrite(MUTED, Permissions::empty(), Permissions::SEND_MESSAGES),
+            overwrite(MODS, Permissions::SEND_MESSAGES, Permissions::empty()),
+        ];
+        let permissions = compute_overwrites(text(), GUILD, USER, &[MODS, MUTED], &overwrites);
+        assert_eq!(permissions, text());
+    }
+
+    #[test]
+    fn removes_permissions_that_depend_on_others() {
+        let hidden = [overwrite(
+            GUILD,
+            Permissions::empty(),
+            Permissions::VIEW_CHANNEL,
+        )];
+        assert_eq!(
+            compute_overwrites(text(), GUILD, USER, &[], &hidden),
+            Permissions::empty()
+        );
+
+        let read_only = [overwrite(
+            GUILD,
+            Permissions::empty(),
+            Permissions::SEND_MESSAGES,
+        )];
+        let base = text() | Permissions::EMBED_LINKS | Permissions::ADD_REACTIONS;
+        assert_eq!(
+            compute_overwrites(base, GUILD, USER, &[], &read_only),
+            Permissions::VIEW_CHANNEL | Permissions::ADD_REACTIONS
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::snowflake::Snowflake;
+
+/// CHANNEL_PINS_UPDATE, sent when a message in the channel is pinned or
+/// unpinned.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChannelPinsUpdate {
+    pub guild_id: Option<Snowflake>,
+    pub channel_id: Snowflake,
+    pub last_pin_timestamp: Option<DateTime<Utc>>,
+}
+
+pub async fn pin(token: &str, channel_id: Snowflake, message_id: Snowflake) -> Result<(), String> {
+    super::api::put(
+        token,
+        &format!("/channels/{}/pins/{}", channel_id, message_id),
+    )
+    .await
+}
+
+pub async fn unpin(
+    token: &str,
+    channel_id: Snowflake,
+    message_id: Snowflake,
+) -> Result<(), String> {
+    super::api::delete(
+        token,
+        &format!("/channels/{}/pins/{}", channel_id, message_id),
+    )
+    .await
+}
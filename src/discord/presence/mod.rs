@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::gateway;
+use super::snowflake::Snowflake;
+use super::types::{ActivityType, Status};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomStatus {
+    pub text: String,
+    /// A standard emoji; custom ones need Nitro.
+    pub emoji: Option<String>,
+    /// Unix milliseconds after which the status is cleared, if ever.
+    pub expires_at: Option<i64>,
+}
+
+impl CustomStatus {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.emoji.is_none()
+    }
+
+    pub fn is_expired(&self, now_ms: i64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now_ms)
+    }
+}
+
+/// What we tell the gateway about ourselves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Presence {
+    pub status: Status,
+    pub custom_status: Option<CustomStatus>,
+    /// Unix milliseconds since which we have been idle, when idle was
+    /// switched on automatically rather than picked.
+    pub afk_since: Option<i64>,
+}
+
+impl Presence {
+    /// Queues op 3 PRESENCE_UPDATE.
+    pub fn send(&self) {
+        let activities: Vec<serde_json::Value> = self
+            .custom_status
+            .iter()
+            .filter(|status| !status.is_empty())
+            .map(|status| {
+                serde_json::json!({
+                    "type": ActivityType::Custom,
+                    "name": "Custom Status",
+                    "state": (!status.text.is_empty()).then_some(&status.text),
+                    "emoji": status.emoji.as_ref().map(|name| serde_json::json!({ "name": name })),
+                })
+            })
+            .collect();
+        gateway::send_command(
+            3,
+            serde_json::json!({
+                "status": self.status,
+                "since": self.afk_since.unwrap_or(0),
+                "activities": activities,
+                "afk": self.afk_since.is_some(),
+            }),
+        );
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ActivityEmoji {
+    pub name: String,
+    pub id: Option<Snowflake>,
+}
+
+/// Something a user is doing: a game, a song, or their custom status.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Activity {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub activity_type: ActivityType,
+    pub state: Option<String>,
+    pub details: Option<String>,
+    pub emoji: Option<ActivityEmoji>,
+}
+
+impl Activity {
+    /// The line Discord shows for the activity, like "Listening to Spotify".
+    pub fn describe(&self) -> String {
+        match self.activity_type {
+            ActivityType::Playing => format!("Playing {}", self.name),
+            ActivityType::Streaming => {
+                format!("Streaming {}", self.details.as_ref().unwrap_or(&self.name))
+            }
+            ActivityType::Listening => format!("Listening to {}", self.name),
+            ActivityType::Watching => format!("Watching {}", self.name),
+            ActivityType::Competing => format!("Competing in {}", self.name),
+            ActivityType::Custom => {
+                let emoji = self.emoji.as_ref().map(|e| e.name.as_str());
+                let parts: Vec<&str> = emoji.into_iter().chain(self.state.as_deref()).collect();
+                parts.join(" ")
+            }
+            ActivityType::Unknown(_) => self.name.clone(),
+        }
+    }
+}
+
+/// Another user's status and what they are doing.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct UserPresence {
+    #[serde(default)]
+    pub status: Status,
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+}
+
+impl UserPresence {
+    /// The activity to show in one line: a game or song before a custom
+    /// status, as Discord does.
+    pub fn headline(&self) -> Option<&Activity> {
+        self.activities
+            .iter()
+            .find(|a| a.activity_type != ActivityType::Custom)
+            .or_else(|| self.activities.first())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PresenceUser {
+    pub id: Snowflake,
+}
+
+/// PRESENCE_UPDATE, and the presences sent in READY and member chunks.
+/// READY's merged friend presences name the user by `user_id` instead.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PresenceUpdate {
+    pub user: Option<PresenceUser>,
+    pub user_id: Option<Snowflake>,
+    #[serde(flatten)]
+    pub presence: UserPresence,
+}
+
+impl PresenceUpdate {
+    pub fn user_id(&self) -> Option<Snowflake> {
+        self.user.as_ref().map(|u| u.id).or(self.user_id)
+    }
+}
+
+/// The latest presence of every user we have heard about. A user's
+/// presence is the same in every guild, so it is keyed by user alone.
+#[derive(Clone, Debug, Default)]
+pub struct Presences(HashMap<Snowflake, UserPresence>);
+
+impl Presences {
+    pub fn insert(&mut self, user_id: Snowflake, presence: UserPresence) {
+        self.0.insert(user_id, presence);
+    }
+
+    pub fn apply(&mut self, update: PresenceUpdate) {
+        if let Some(user_id) = update.user_id() {
+            self.insert(user_id, update.presence);
+        }
+    }
+
+    pub fn get(&self, user_id: Snowflake) -> Option<&UserPresence> {
+        self.0.get(&user_id)
+    }
+
+    pub fn status(&self, user_id: Snowflake) -> Status {
+        self.get(user_id).map(|p| p.status).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_presence_updates() {
+        let mut presences = Presences::default();
+        presences.apply(
+            serde_json::from_value(serde_json::json!({
+                "user": {"id": "10"},
+                "guild_id": "1",
+                "status": "dnd",
+                "activities": [
+                    {"name": "Custom Status", "type": 4, "state": "busy", "emoji": {"name": "🔥"}},
+                    {"name": "Spotify", "type": 2, "details": "Song"}
+                ]
+            }))
+            .unwrap(),
+        );
+        presences.apply(
+            serde_json::from_value(serde_json::json!({
+                "user_id": "11",
+                "status": "idle",
+                "activities": [{"name": "Factorio", "type": 0}]
+            }))
+            .unwrap(),
+        );
+        let presence = presences.get(Snowflake(10)).unwrap();
+        assert_eq!(presence.status, Status::Dnd);
+        assert_eq!(
+            presence.headline().unwrap().describe(),
+            "Listening to Spotify"
+        );
+        assert_eq!(presence.activities[0].describe(), "🔥 busy");
+        assert_eq!(presences.status(Snowflake(11)), Status::Idle);
+        assert_eq!(presences.status(Snowflake(12)), Status::Offline);
+    }
+}
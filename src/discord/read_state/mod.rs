@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::snowflake::Snowflake;
+use super::types::ChannelType;
+
+/// READY's `read_state`. Newer gateway versions wrap the entries in an
+/// object; older ones send the bare list.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ReadStatePayload {
+    Versioned { entries: Vec<ReadStateEntry> },
+    List(Vec<ReadStateEntry>),
+}
+
+impl Default for ReadStatePayload {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+impl ReadStatePayload {
+    pub fn entries(&self) -> &[ReadStateEntry] {
+        match self {
+            Self::Versioned { entries } | Self::List(entries) => entries,
+        }
+    }
+}
+
+/// The newest message we have read in a channel.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReadStateEntry {
+    /// The channel's id.
+    pub id: Snowflake,
+    pub last_message_id: Option<Snowflake>,
+}
+
+/// A guild as READY describes it: enough of its channels to tell whether
+/// anything in it is unread.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReadyGuild {
+    pub id: Snowflake,
+    #[serde(default)]
+    pub channels: Vec<ReadyChannel>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReadyChannel {
+    pub id: Snowflake,
+    #[serde(rename = "type")]
+    pub channel_type: ChannelType,
+    pub last_message_id: Option<Snowflake>,
+}
+
+/// MESSAGE_ACK, sent when another of our sessions reads a channel.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MessageAck {
+    pub channel_id: Snowflake,
+    pub message_id: Snowflake,
+}
+
+#[derive(Serialize)]
+struct BulkAck {
+    read_states: Vec<BulkAckEntry>,
+}
+
+#[derive(Serialize)]
+struct BulkAckEntry {
+    channel_id: Snowflake,
+    message_id: Snowflake,
+}
+
+/// What we have read against what has been sent, per channel.
+///
+/// A channel is unread when its newest message is past the last one we read.
+/// Channels we have never read have no read state and count as read, as
+/// Discord treats them, so joining a busy guild does not light it all up.
+#[derive(Default)]
+pub struct ReadStates {
+    last_read: HashMap<Snowflake, Snowflake>,
+    last_message: HashMap<Snowflake, Snowflake>,
+    /// Text channels of each guild, for rolling unread state up to guilds.
+    guild_channels: HashMap<Snowflake, Vec<Snowflake>>,
+}
+
+impl ReadStates {
+    /// Starts over from READY.
+    pub fn load(payload: &ReadStatePayload, guilds: &[ReadyGuild]) -> Self {
+        let mut states = Self::default();
+        for entry in payload.entries() {
+            if let Some(message_id) = entry.last_message_id {
+                states.last_read.insert(entry.id, message_id);
+            }
+        }
+        for guild in guilds {
+            let channels = guild
+                .channels
+                .iter()
+                .filter(|c| c.channel_type.is_text())
+                .map(|channel| {
+                    if let Some(message_id) = channel.last_message_id {
+                        states.last_message.insert(channel.id, message_id);
+                    }
+                    channel.id
+                })
+                .collect();
+            states.guild_channels.insert(guild.id, channels);
+        }
+        states
+    }
+
+    /// Records a new message in `channel_id`.
+    pub fn message_created(&mut self, channel_id: Snowflake, message_id: Snowflake) {
+        let last = self.last_message.entry(channel_id).or_insert(message_id);
+        *last = (*last).max(message_id);
+    }
+
+    /// Records that we have read up to `message_id`. Returns whether that is
+    /// further than before, and so worth telling the server.
+    pub fn read(&mut self, channel_id: Snowflake, message_id: Snowflake) -> bool {
+        self.message_created(channel_id, message_id);
+        match self.last_read.get(&channel_id) {
+            Some(read) if *read >= message_id => false,
+            _ => {
+                self.last_read.insert(channel_id, message_id);
+                true
+            }
+        }
+    }
+
+    pub fn last_message(&self, channel_id: Snowflake) -> Option<Snowflake> {
+        self.last_message.get(&channel_id).copied()
+    }
+
+    pub fn is_unread(&self, channel_id: Snowflake) -> bool {
+        match (
+            self.last_read.get(&channel_id),
+            self.last_message.get(&channel_id),
+        ) {
+            (Some(read), Some(last)) => last > read,
+            _ => false,
+        }
+    }
+
+    pub fn guild_unread(&self, guild_id: Snowflake) -> bool {
+        self.guild_channels
+            .get(&guild_id)
+            .is_some_and(|channels| channels.iter().any(|c| self.is_unread(*c)))
+    }
+
+    /// Marks every channel of the guild read, returning the `(channel,
+    /// message)` pairs to acknowledge.
+    pub fn read_guild(&mut self, guild_id: Snowflake) -> Vec<(Snowflake, Snowflake)> {
+        let channels = self
+            .guild_channels
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default();
+        let mut reads = Vec::new();
+        for channel_id in channels {
+            if !self.is_unread(channel_id) {
+                continue;
+            }
+            if let Some(message_id) = self.last_message(channel_id) {
+                self.read(channel_id, message_id);
+                reads.push((channel_id, message_id));
+            }
+        }
+        reads
+    }
+}
+
+/// Tells the server we have read `channel_id` up to `message_id`.
+pub async fn ack(token: &str, channel_id: Snowflake, message_id: Snowflake) -> Result<(), String> {
+    super::api::post(
+        token,
+        &format!("/channels/{}/messages/{}/ack", channel_id, message_id),
+        &serde_json::json!({ "token": null }),
+    )
+    .await
+}
+
+/// Acknowledges several channels in one request.
+pub async fn ack_bulk(token: &str, reads: Vec<(Snowflake, Snowflake)>) -> Result<(), String> {
+    let body = BulkAck {
+        read_states: reads
+            .into_iter()
+            .map(|(channel_id, message_id)| BulkAckEntry {
+                channel_id,
+                message_id,
+            })
+            .collect(),
+    };
+    super::api::post(token, "/read-states/ack-bulk", &body).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_unread_channels_and_guilds() {
+        let payload: ReadStatePayload = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "partial": false,
+            "entries": [{"id": "10", "last_message_id": "100"}]
+        }))
+        .unwrap();
+        let guilds: Vec<ReadyGuild> = serde_json::from_value(serde_json::json!([{
+            "id": "1",
+            "channels": [
+                {"id": "10", "type": 0, "last_message_id": "100"},
+                {"id": "11", "type": 0, "last_message_id": "200"}
+            ]
+        }]))
+        .unwrap();
+        let mut states = ReadStates::load(&payload, &guilds);
+        // Never read, so not unread.
+        assert!(!states.is_unread(Snowflake(11)));
+        assert!(!states.guild_unread(Snowflake(1)));
+
+        states.message_created(Snowflake(10), Snowflake(150));
+        assert!(states.is_unread(Snowflake(10)));
+        assert!(states.guild_unread(Snowflake(1)));
+
+        assert_eq!(
+            states.read_guild(Snowflake(1)),
+            vec![(Snowflake(10), Snowflake(150))]
+        );
+        assert!(!states.guild_unread(Snowflake(1)));
+        assert!(!states.read(Snowflake(10), Snowflake(120)));
+    }
+}
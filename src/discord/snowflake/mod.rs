@@ -0,0 +1,160 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Milliseconds between the Unix epoch and the first second of 2015, which
+/// snowflake timestamps are relative to.
+pub const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+/// A Discord id. Sent over the wire as a string, but ordered and compared as
+/// the 64-bit integer it encodes, so sorting by id sorts by creation time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snowflake(pub u64);
+
+impl Snowflake {
+    /// The smallest snowflake that could have been created at `time`, useful
+    /// as a `before`/`after` cursor when paginating by date.
+    pub fn from_time(time: SystemTime) -> Self {
+        let ms = time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self(ms.saturating_sub(DISCORD_EPOCH) << 22)
+    }
+
+    /// Milliseconds since the Unix epoch at which this id was created.
+    pub fn timestamp_ms(self) -> u64 {
+        (self.0 >> 22) + DISCORD_EPOCH
+    }
+
+    pub fn created_at(self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp_ms())
+    }
+
+    /// How long ago this id was created, or zero if it is in the future.
+    pub fn age(self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.created_at())
+            .unwrap_or_default()
+    }
+
+    pub fn worker_id(self) -> u8 {
+        ((self.0 >> 17) & 0x1f) as u8
+    }
+
+    pub fn process_id(self) -> u8 {
+        ((self.0 >> 12) & 0x1f) as u8
+    }
+
+    pub fn increment(self) -> u16 {
+        (self.0 & 0xfff) as u16
+    }
+}
+
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Snowflake {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl From<u64> for Snowflake {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for Snowflake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Snowflake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SnowflakeVisitor;
+
+        impl<'de> Visitor<'de> for SnowflakeVisitor {
+            type Value = Snowflake;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a snowflake as a string or integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Snowflake, E> {
+                Ok(Snowflake(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Snowflake, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(SnowflakeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example from Discord's API reference.
+    const EXAMPLE: Snowflake = Snowflake(175928847299117063);
+
+    #[test]
+    fn serializes_as_a_string() {
+        let id: Snowflake = serde_json::from_str("\"175928847299117063\"").unwrap();
+        assert_eq!(id, EXAMPLE);
+        let id: Snowflake = serde_json::from_str("175928847299117063").unwrap();
+        assert_eq!(id, EXAMPLE);
+        assert_eq!(
+            serde_json::to_string(&EXAMPLE).unwrap(),
+            "\"175928847299117063\""
+        );
+        assert!(serde_json::from_str::<Snowflake>("\"abc\"").is_err());
+    }
+
+    #[test]
+    fn decodes_its_fields() {
+        assert_eq!(EXAMPLE.timestamp_ms(), 1_462_015_105_796);
+        assert_eq!(EXAMPLE.worker_id(), 1);
+        assert_eq!(EXAMPLE.process_id(), 0);
+        assert_eq!(EXAMPLE.increment(), 7);
+        assert_eq!(
+            EXAMPLE.created_at(),
+            UNIX_EPOCH + Duration::from_millis(1_462_015_105_796)
+        );
+    }
+
+    #[test]
+    fn from_time_round_trips() {
+        let time = EXAMPLE.created_at();
+        let cursor = Snowflake::from_time(time);
+        assert_eq!(cursor.created_at(), time);
+        // The cursor is the first id of that millisecond.
+        assert!(cursor <= EXAMPLE);
+        assert_eq!(cursor.0, EXAMPLE.0 >> 22 << 22);
+        assert_eq!(Snowflake::from_time(UNIX_EPOCH), Snowflake(0));
+        assert_eq!(Snowflake(0).timestamp_ms(), DISCORD_EPOCH);
+    }
+
+    #[test]
+    fn orders_numerically() {
+        // As strings, "9" would sort after "10".
+        let mut ids: Vec<Snowflake> = ["10", "9", "175928847299117063"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, [Snowflake(9), Snowflake(10), EXAMPLE]);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::snowflake::Snowflake;
+use super::types::ChannelType;
+
+/// How long a thread may sit without messages before Discord archives it,
+/// in minutes, with the names Discord gives the choices.
+pub const AUTO_ARCHIVE_DURATIONS: [(u64, &str); 4] = [
+    (60, "1 hour"),
+    (24 * 60, "24 hours"),
+    (3 * 24 * 60, "3 days"),
+    (7 * 24 * 60, "1 week"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadMetadata {
+    pub archived: bool,
+    pub auto_archive_duration: u64,
+    pub archive_timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub locked: bool,
+}
+
+/// A thread channel (types 10 to 12). Threads live under a text channel and
+/// lack the position and overwrites of ordinary channels, so they get their
+/// own type.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thread {
+    pub id: Snowflake,
+    pub guild_id: Option<Snowflake>,
+    pub parent_id: Option<Snowflake>,
+    pub owner_id: Option<Snowflake>,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub channel_type: ChannelType,
+    pub last_message_id: Option<Snowflake>,
+    #[serde(default)]
+    pub message_count: u64,
+    #[serde(default)]
+    pub member_count: u64,
+    pub thread_metadata: Option<ThreadMetadata>,
+    /// For forum posts, the ids of the forum's tags it is filed under.
+    #[serde(default)]
+    pub applied_tags: Vec<Snowflake>,
+}
+
+impl Thread {
+    pub fn is_archived(&self) -> bool {
+        self.thread_metadata.as_ref().is_some_and(|m| m.archived)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.thread_metadata.as_ref().is_some_and(|m| m.locked)
+    }
+}
+
+/// Our membership of a thread.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThreadMember {
+    /// The thread's id.
+    pub id: Option<Snowflake>,
+    pub user_id: Option<Snowflake>,
+}
+
+/// The REST answer for active or archived threads.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ThreadList {
+    #[serde(default)]
+    pub threads: Vec<Thread>,
+    /// The threads we have joined, among `threads`.
+    #[serde(default)]
+    pub members: Vec<ThreadMember>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+/// THREAD_LIST_SYNC: every active thread in the given parent channels, or in
+/// the whole guild if none are named.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThreadListSync {
+    pub guild_id: Snowflake,
+    pub channel_ids: Option<Vec<Snowflake>>,
+    #[serde(default)]
+    pub threads: Vec<Thread>,
+    #[serde(default)]
+    pub members: Vec<ThreadMember>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThreadDelete {
+    pub id: Snowflake,
+    pub guild_id: Option<Snowflake>,
+    pub parent_id: Option<Snowflake>,
+}
+
+/// THREAD_MEMBERS_UPDATE, sent when people join or leave a thread.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThreadMembersUpdate {
+    pub id: Snowflake,
+    pub guild_id: Snowflake,
+    pub member_count: u64,
+    #[serde(default)]
+    pub added_members: Vec<ThreadMember>,
+    #[serde(default)]
+    pub removed_member_ids: Vec<Snowflake>,
+}
+
+/// The active threads of one guild and which of them we are in.
+#[derive(Clone, Debug, Default)]
+pub struct Threads {
+    pub guild_id: Option<Snowflake>,
+    active: Vec<Thread>,
+    joined: HashSet<Snowflake>,
+}
+
+impl Threads {
+    pub fn new(guild_id: Snowflake, list: ThreadList) -> Self {
+        let mut threads = Self {
+            guild_id: Some(guild_id),
+            ..Self::default()
+        };
+        threads.add(list.threads, &list.members);
+        threads
+    }
+
+    fn add(&mut self, threads: Vec<Thread>, members: &[ThreadMember]) {
+        self.joined.extend(members.iter().filter_map(|m| m.id));
+        for thread in threads {
+            self.upsert(thread);
+        }
+    }
+
+    /// Adds or updates a thread. Archived threads leave the active list.
+    pub fn upsert(&mut self, thread: Thread) {
+        if thread.guild_id.is_some() && thread.guild_id != self.guild_id {
+            return;
+        }
+        self.active.retain(|t| t.id != thread.id);
+        if !thread.is_archived() {
+            self.active.push(thread);
+        }
+    }
+
+    pub fn remove(&mut self, thread_id: Snowflake) {
+        self.active.retain(|t| t.id != thread_id);
+        self.joined.remove(&thread_id);
+    }
+
+    pub fn sync(&mut self, sync: ThreadListSync) {
+        if Some(sync.guild_id) != self.guild_id {
+            return;
+        }
+        match &sync.channel_ids {
+            Some(parents) => self
+                .active
+                .retain(|t| !t.parent_id.is_some_and(|p| parents.contains(&p))),
+            None => self.active.clear(),
+        }
+        self.add(sync.threads, &sync.members);
+    }
+
+    pub fn members_update(&mut self, update: ThreadMembersUpdate, user_id: Option<Snowflake>) {
+        if Some(update.guild_id) != self.guild_id {
+            return;
+        }
+        if let Some(thread) = self.active.iter_mut().find(|t| t.id == update.id) {
+            thread.member_count = update.member_count;
+        }
+        if let Some(user_id) = user_id {
+            if update
+                .added_members
+                .iter()
+                .any(|m| m.user_id == Some(user_id))
+            {
+                self.joined.insert(update.id);
+            }
+            if update.removed_member_ids.contains(&user_id) {
+                self.joined.remove(&update.id);
+            }
+        }
+    }
+
+    /// Counts a new message in a thread and moves it up its parent's list.
+    pub fn message_created(&mut self, channel_id: Snowflake, message_id: Snowflake) {
+        if let Some(thread) = self.active.iter_mut().find(|t| t.id == channel_id) {
+            thread.last_message_id = Some(message_id);
+            thread.message_count += 1;
+        }
+    }
+
+    pub fn get(&self, thread_id: Snowflake) -> Option<&Thread> {
+        self.active.iter().find(|t| t.id == thread_id)
+    }
+
+    pub fn is_joined(&self, thread_id: Snowflake) -> bool {
+        self.joined.contains(&thread_id)
+    }
+
+    /// The active threads under `parent_id`, most recently active first.
+    pub fn under(&self, parent_id: Snowflake) -> Vec<&Thread> {
+        let mut threads: Vec<&Thread> = self
+            .active
+            .iter()
+            .filter(|t| t.parent_id == Some(parent_id))
+            .collect();
+        threads.sort_by_key(|t| std::cmp::Reverse(t.last_message_id.unwrap_or(t.id)));
+        threads
+    }
+}
+
+/// The guild's active threads. Only bots may list them this way; user
+/// accounts are sent THREAD_LIST_SYNC instead.
+pub async fn active(token: &str, guild_id: Snowflake) -> Result<ThreadList, String> {
+    super::api::get(
+        token,
+        &format!("/guilds/{}/threads/active", guild_id),
+        "threads",
+    )
+    .await
+}
+
+/// A page of `channel_id`'s archived public threads, newest first, archived
+/// before `before` if given.
+pub async fn archived(
+    token: &str,
+    channel_id: Snowflake,
+    before: Option<DateTime<Utc>>,
+) -> Result<ThreadList, String> {
+    let mut path = format!("/channels/{}/threads/archived/public?limit=50", channel_id);
+    if let Some(before) = before {
+        // With a `Z` rather than `+00:00`, which would need escaping.
+        let before = before.to_rfc3339_opts(SecondsFormat::Secs, true);
+        path.push_str(&format!("&before={}", before));
+    }
+    super::api::get(token, &path, "archived threads").await
+}
+
+/// Starts a public thread on a message.
+pub async fn start_from_message(
+    token: &str,
+    channel_id: Snowflake,
+    message_id: Snowflake,
+    name: &str,
+    auto_archive_duration: u64,
+) -> Result<Thread, String> {
+    super::api::create(
+        token,
+        &format!("/channels/{}/messages/{}/threads", channel_id, message_id),
+        &serde_json::json!({
+            "name": name,
+            "auto_archive_duration": auto_archive_duration,
+        }),
+        "thread",
+    )
+    .await
+}
+
+pub async fn set_archived(
+    token: &str,
+    thread_id: Snowflake,
+    archived: bool,
+) -> Result<Thread, String> {
+    super::api::patch(
+        token,
+        &format!("/channels/{}", thread_id),
+        &serde_json::json!({ "archived": archived }),
+        "thread",
+    )
+    .await
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A thread as the API sends it. Forum posts are built from it too.
+    pub(crate) fn thread(
+        id: u64,
+        parent: u64,
+        last_message: Option<u64>,
+        archived: bool,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "id": id.to_string(),
+            "guild_id": "1",
+            "parent_id": parent.to_string(),
+            "owner_id": "9",
+            "name": format!("thread {}", id),
+            "type": 11,
+            "last_message_id": last_message.map(|id| id.to_string()),
+            "message_count": 3,
+            "member_count": 2,
+            "thread_metadata": {
+                "archived": archived,
+                "auto_archive_duration": 1440,
+                "archive_timestamp": "2023-01-01T00:00:00+00:00",
+                "locked": false
+            }
+        })
+    }
+
+    fn ids(threads: &Threads, parent: u64) -> Vec<u64> {
+        threads
+            .under(Snowflake(parent))
+            .iter()
+            .map(|t| t.id.0)
+            .collect()
+    }
+
+    #[test]
+    fn tracks_active_threads() {
+        let list: ThreadList = serde_json::from_value(serde_json::json!({
+            "threads": [thread(10, 2, Some(100), false), thread(11, 2, Some(200), false), thread(12, 3, Some(50), false)],
+            "members": [{"id": "10", "user_id": "9"}]
+        }))
+        .unwrap();
+        let mut threads = Threads::new(Snowflake(1), list);
+        assert_eq!(ids(&threads, 2), [11, 10]);
+        assert!(threads.is_joined(Snowflake(10)));
+
+        let archived: Thread = serde_json::from_value(thread(11, 2, Some(200), true)).unwrap();
+        threads.upsert(archived);
+        assert_eq!(ids(&threads, 2), [10]);
+
+        let sync: ThreadListSync = serde_json::from_value(serde_json::json!({
+            "guild_id": "1",
+            "channel_ids": ["3"],
+            "threads": [thread(13, 3, Some(60), false)]
+        }))
+        .unwrap();
+        threads.sync(sync);
+        assert_eq!(ids(&threads, 3), [13]);
+        assert_eq!(ids(&threads, 2), [10]);
+
+        let update: ThreadMembersUpdate = serde_json::from_value(serde_json::json!({
+            "id": "13",
+            "guild_id": "1",
+            "member_count": 5,
+            "added_members": [{"id": "13", "user_id": "9"}]
+        }))
+        .unwrap();
+        threads.members_update(update, Some(Snowflake(9)));
+        assert!(threads.is_joined(Snowflake(13)));
+        assert_eq!(threads.get(Snowflake(13)).unwrap().member_count, 5);
+    }
+}
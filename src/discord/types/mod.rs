@@ -0,0 +1,457 @@
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum ChannelType {
+    GuildText,
+    Dm,
+    GuildVoice,
+    GroupDm,
+    GuildCategory,
+    GuildAnnouncement,
+    AnnouncementThread,
+    PublicThread,
+    PrivateThread,
+    GuildStageVoice,
+    GuildDirectory,
+    GuildForum,
+    Unknown(u64),
+}
+
+impl From<u64> for ChannelType {
+    fn from(value: u64) -> Self {
+        match value {
+            0 => Self::GuildText,
+            1 => Self::Dm,
+            2 => Self::GuildVoice,
+            3 => Self::GroupDm,
+            4 => Self::GuildCategory,
+            5 => Self::GuildAnnouncement,
+            10 => Self::AnnouncementThread,
+            11 => Self::PublicThread,
+            12 => Self::PrivateThread,
+            13 => Self::GuildStageVoice,
+            14 => Self::GuildDirectory,
+            15 => Self::GuildForum,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<ChannelType> for u64 {
+    fn from(value: ChannelType) -> Self {
+        match value {
+            ChannelType::GuildText => 0,
+            ChannelType::Dm => 1,
+            ChannelType::GuildVoice => 2,
+            ChannelType::GroupDm => 3,
+            ChannelType::GuildCategory => 4,
+            ChannelType::GuildAnnouncement => 5,
+            ChannelType::AnnouncementThread => 10,
+            ChannelType::PublicThread => 11,
+            ChannelType::PrivateThread => 12,
+            ChannelType::GuildStageVoice => 13,
+            ChannelType::GuildDirectory => 14,
+            ChannelType::GuildForum => 15,
+            ChannelType::Unknown(other) => other,
+        }
+    }
+}
+
+impl ChannelType {
+    pub fn is_thread(self) -> bool {
+        matches!(
+            self,
+            Self::AnnouncementThread | Self::PublicThread | Self::PrivateThread
+        )
+    }
+
+    pub fn is_voice(self) -> bool {
+        matches!(self, Self::GuildVoice | Self::GuildStageVoice)
+    }
+
+    pub fn is_private(self) -> bool {
+        matches!(self, Self::Dm | Self::GroupDm)
+    }
+
+    /// Whether the channel has a message history we can open.
+    pub fn is_text(self) -> bool {
+        matches!(
+            self,
+            Self::GuildText | Self::GuildAnnouncement | Self::Dm | Self::GroupDm
+        ) || self.is_thread()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum MessageType {
+    Default,
+    RecipientAdd,
+    RecipientRemove,
+    Call,
+    ChannelNameChange,
+    ChannelIconChange,
+    ChannelPinnedMessage,
+    UserJoin,
+    GuildBoost,
+    GuildBoostTier1,
+    GuildBoostTier2,
+    GuildBoostTier3,
+    ChannelFollowAdd,
+    GuildDiscoveryDisqualified,
+    GuildDiscoveryRequalified,
+    GuildDiscoveryGracePeriodInitialWarning,
+    GuildDiscoveryGracePeriodFinalWarning,
+    ThreadCreated,
+    Reply,
+    ChatInputCommand,
+    ThreadStarterMessage,
+    GuildInviteReminder,
+    ContextMenuCommand,
+    AutoModerationAction,
+    Unknown(u64),
+}
+
+impl From<u64> for MessageType {
+    fn from(value: u64) -> Self {
+        match value {
+            0 => Self::Default,
+            1 => Self::RecipientAdd,
+            2 => Self::RecipientRemove,
+            3 => Self::Call,
+            4 => Self::ChannelNameChange,
+            5 => Self::ChannelIconChange,
+            6 => Self::ChannelPinnedMessage,
+            7 => Self::UserJoin,
+            8 => Self::GuildBoost,
+            9 => Self::GuildBoostTier1,
+            10 => Self::GuildBoostTier2,
+            11 => Self::GuildBoostTier3,
+            12 => Self::ChannelFollowAdd,
+            14 => Self::GuildDiscoveryDisqualified,
+            15 => Self::GuildDiscoveryRequalified,
+            16 => Self::GuildDiscoveryGracePeriodInitialWarning,
+            17 => Self::GuildDiscoveryGracePeriodFinalWarning,
+            18 => Self::ThreadCreated,
+            19 => Self::Reply,
+            20 => Self::ChatInputCommand,
+            21 => Self::ThreadStarterMessage,
+            22 => Self::GuildInviteReminder,
+            23 => Self::ContextMenuCommand,
+            24 => Self::AutoModerationAction,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<MessageType> for u64 {
+    fn from(value: MessageType) -> Self {
+        match value {
+            MessageType::Default => 0,
+            MessageType::RecipientAdd => 1,
+            MessageType::RecipientRemove => 2,
+            MessageType::Call => 3,
+            MessageType::ChannelNameChange => 4,
+            MessageType::ChannelIconChange => 5,
+            MessageType::ChannelPinnedMessage => 6,
+            MessageType::UserJoin => 7,
+            MessageType::GuildBoost => 8,
+            MessageType::GuildBoostTier1 => 9,
+            MessageType::GuildBoostTier2 => 10,
+            MessageType::GuildBoostTier3 => 11,
+            MessageType::ChannelFollowAdd => 12,
+            MessageType::GuildDiscoveryDisqualified => 14,
+            MessageType::GuildDiscoveryRequalified => 15,
+            MessageType::GuildDiscoveryGracePeriodInitialWarning => 16,
+            MessageType::GuildDiscoveryGracePeriodFinalWarning => 17,
+            MessageType::ThreadCreated => 18,
+            MessageType::Reply => 19,
+            MessageType::ChatInputCommand => 20,
+            MessageType::ThreadStarterMessage => 21,
+            MessageType::GuildInviteReminder => 22,
+            MessageType::ContextMenuCommand => 23,
+            MessageType::AutoModerationAction => 24,
+            MessageType::Unknown(other) => other,
+        }
+    }
+}
+
+impl MessageType {
+    /// System messages are generated by Discord rather than written by the
+    /// author, and are shown as a single line instead of `author: content`.
+    pub fn is_system(self) -> bool {
+        !matches!(
+            self,
+            Self::Default
+                | Self::Reply
+                | Self::ChatInputCommand
+                | Self::ContextMenuCommand
+                | Self::ThreadStarterMessage
+                | Self::Unknown(_)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum ComponentType {
+    ActionRow,
+    Button,
+    StringSelect,
+    TextInput,
+    UserSelect,
+    RoleSelect,
+    MentionableSelect,
+    ChannelSelect,
+    Unknown(u64),
+}
+
+impl From<u64> for ComponentType {
+    fn from(value: u64) -> Self {
+        match value {
+            1 => Self::ActionRow,
+            2 => Self::Button,
+            3 => Self::StringSelect,
+            4 => Self::TextInput,
+            5 => Self::UserSelect,
+            6 => Self::RoleSelect,
+            7 => Self::MentionableSelect,
+            8 => Self::ChannelSelect,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<ComponentType> for u64 {
+    fn from(value: ComponentType) -> Self {
+        match value {
+            ComponentType::ActionRow => 1,
+            ComponentType::Button => 2,
+            ComponentType::StringSelect => 3,
+            ComponentType::TextInput => 4,
+            ComponentType::UserSelect => 5,
+            ComponentType::RoleSelect => 6,
+            ComponentType::MentionableSelect => 7,
+            ComponentType::ChannelSelect => 8,
+            ComponentType::Unknown(other) => other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum ButtonStyle {
+    Primary,
+    Secondary,
+    Success,
+    Danger,
+    /// Opens the button's `url` instead of sending an interaction.
+    Link,
+    Unknown(u64),
+}
+
+impl From<u64> for ButtonStyle {
+    fn from(value: u64) -> Self {
+        match value {
+            1 => Self::Primary,
+            2 => Self::Secondary,
+            3 => Self::Success,
+            4 => Self::Danger,
+            5 => Self::Link,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<ButtonStyle> for u64 {
+    fn from(value: ButtonStyle) -> Self {
+        match value {
+            ButtonStyle::Primary => 1,
+            ButtonStyle::Secondary => 2,
+            ButtonStyle::Success => 3,
+            ButtonStyle::Danger => 4,
+            ButtonStyle::Link => 5,
+            ButtonStyle::Unknown(other) => other,
+        }
+    }
+}
+
+/// A user's online status. Invisible users appear offline to everyone else.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Online,
+    Idle,
+    Dnd,
+    Invisible,
+    #[default]
+    #[serde(other)]
+    Offline,
+}
+
+impl Status {
+    pub fn is_online(self) -> bool {
+        !matches!(self, Self::Invisible | Self::Offline)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum ActivityType {
+    Playing,
+    Streaming,
+    Listening,
+    Watching,
+    /// A custom status, which carries its text in `state`.
+    Custom,
+    Competing,
+    Unknown(u64),
+}
+
+impl From<u64> for ActivityType {
+    fn from(value: u64) -> Self {
+        match value {
+            0 => Self::Playing,
+            1 => Self::Streaming,
+            2 => Self::Listening,
+            3 => Self::Watching,
+            4 => Self::Custom,
+            5 => Self::Competing,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<ActivityType> for u64 {
+    fn from(value: ActivityType) -> Self {
+        match value {
+            ActivityType::Playing => 0,
+            ActivityType::Streaming => 1,
+            ActivityType::Listening => 2,
+            ActivityType::Watching => 3,
+            ActivityType::Custom => 4,
+            ActivityType::Competing => 5,
+            ActivityType::Unknown(other) => other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum StickerFormat {
+    Png,
+    /// Animated PNG. Decoders that do not know APNG show its first frame.
+    Apng,
+    /// A Lottie animation, which is JSON rather than an image.
+    Lottie,
+    Gif,
+    Unknown(u64),
+}
+
+impl From<u64> for StickerFormat {
+    fn from(value: u64) -> Self {
+        match value {
+            1 => Self::Png,
+            2 => Self::Apng,
+            3 => Self::Lottie,
+            4 => Self::Gif,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<StickerFormat> for u64 {
+    fn from(value: StickerFormat) -> Self {
+        match value {
+            StickerFormat::Png => 1,
+            StickerFormat::Apng => 2,
+            StickerFormat::Lottie => 3,
+            StickerFormat::Gif => 4,
+            StickerFormat::Unknown(other) => other,
+        }
+    }
+}
+
+bitflags! {
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct MessageFlags: u64 {
+        const CROSSPOSTED = 1 << 0;
+        const IS_CROSSPOST = 1 << 1;
+        const SUPPRESS_EMBEDS = 1 << 2;
+        const SOURCE_MESSAGE_DELETED = 1 << 3;
+        const URGENT = 1 << 4;
+        const HAS_THREAD = 1 << 5;
+        const EPHEMERAL = 1 << 6;
+        const LOADING = 1 << 7;
+        const FAILED_TO_MENTION_SOME_ROLES_IN_THREAD = 1 << 8;
+    }
+}
+
+bitflags! {
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct UserFlags: u64 {
+        const STAFF = 1 << 0;
+        const PARTNER = 1 << 1;
+        const HYPESQUAD = 1 << 2;
+        const BUG_HUNTER_LEVEL_1 = 1 << 3;
+        const HYPESQUAD_ONLINE_HOUSE_1 = 1 << 6;
+        const HYPESQUAD_ONLINE_HOUSE_2 = 1 << 7;
+        const HYPESQUAD_ONLINE_HOUSE_3 = 1 << 8;
+        const PREMIUM_EARLY_SUPPORTER = 1 << 9;
+        const TEAM_PSEUDO_USER = 1 << 10;
+        const BUG_HUNTER_LEVEL_2 = 1 << 14;
+        const VERIFIED_BOT = 1 << 16;
+        const VERIFIED_DEVELOPER = 1 << 17;
+        const CERTIFIED_MODERATOR = 1 << 18;
+        const BOT_HTTP_INTERACTIONS = 1 << 19;
+        const ACTIVE_DEVELOPER = 1 << 22;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_round_trip_unknown_values() {
+        let channel: ChannelType = serde_json::from_str("15").unwrap();
+        assert_eq!(channel, ChannelType::GuildForum);
+        let channel: ChannelType = serde_json::from_str("99").unwrap();
+        assert_eq!(channel, ChannelType::Unknown(99));
+        assert_eq!(serde_json::to_string(&channel).unwrap(), "99");
+
+        let message: MessageType = serde_json::from_str("19").unwrap();
+        assert_eq!(message, MessageType::Reply);
+        // 13 is unused by Discord, so it must not be mistaken for a neighbour.
+        let message: MessageType = serde_json::from_str("13").unwrap();
+        assert_eq!(message, MessageType::Unknown(13));
+        assert_eq!(serde_json::to_string(&message).unwrap(), "13");
+    }
+
+    #[test]
+    fn system_messages() {
+        assert!(MessageType::UserJoin.is_system());
+        assert!(MessageType::ChannelPinnedMessage.is_system());
+        assert!(MessageType::ThreadCreated.is_system());
+        assert!(!MessageType::Default.is_system());
+        assert!(!MessageType::Reply.is_system());
+        assert!(!MessageType::ChatInputCommand.is_system());
+        // Types we do not know yet are shown like ordinary messages.
+        assert!(!MessageType::Unknown(99).is_system());
+    }
+
+    #[test]
+    fn flags_are_plain_integers() {
+        let flags: MessageFlags = serde_json::from_str("80").unwrap();
+        assert_eq!(flags, MessageFlags::EPHEMERAL | MessageFlags::URGENT);
+        assert_eq!(serde_json::to_string(&flags).unwrap(), "80");
+
+        let flags: UserFlags = serde_json::from_str("4194305").unwrap();
+        assert_eq!(flags, UserFlags::STAFF | UserFlags::ACTIVE_DEVELOPER);
+        assert_eq!(serde_json::to_string(&flags).unwrap(), "4194305");
+    }
+}
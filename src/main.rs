@@ -1,7 +1,25 @@
 pub mod discord;
+mod ui;
 
 use async_std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use discord::commands::ApplicationCommand;
+use discord::forum::{ForumTag, SortOrder};
 use discord::gateway::GatewayClient;
+use discord::interactions::{CommandInteraction, ComponentInteraction, InteractionResult};
+use discord::members::{Member, MemberList, MemberListUpdate, MembersChunk};
+use discord::permissions::{GuildPermissions, PermissionOverwrite, Permissions, Role};
+use discord::pins::ChannelPinsUpdate;
+use discord::presence::{Presence, PresenceUpdate, Presences};
+use discord::read_state::{MessageAck, ReadStatePayload, ReadStates, ReadyGuild};
+use discord::snowflake::Snowflake;
+use discord::threads::{
+    Thread, ThreadDelete, ThreadList, ThreadListSync, ThreadMembersUpdate, Threads,
+};
+use discord::types::{
+    ButtonStyle, ChannelType, ComponentType, MessageFlags, MessageType, Status, StickerFormat,
+    UserFlags,
+};
 use eframe::{
     epaint::ahash::{HashMap, HashMapExt},
     run_native, App,
@@ -11,38 +29,153 @@ use poll_promise::Promise;
 use rand::distributions::{Alphanumeric, DistString};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ui::settings::Settings;
+use ui::{Action, RenderContext};
 
 lazy_static::lazy_static! {
     // static ref GATEWAY_CLIENT: Arc<GatewayClient> = Arc::new(GatewayClient::new());
-    static ref CHANNEL_CACHE: Arc<Mutex<HashMap<String, Vec<Channel>>>> = Arc::new(Mutex::new(HashMap::new()));
-    static ref MESSAGE_CACHE: Arc<Mutex<HashMap<String, Vec<Message>>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref CHANNEL_CACHE: Arc<Mutex<HashMap<Snowflake, Vec<Channel>>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref MESSAGE_CACHE: Arc<Mutex<HashMap<Snowflake, Vec<Message>>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref PERMISSION_CACHE: Arc<Mutex<HashMap<Snowflake, GuildPermissions>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref ROLE_CACHE: Arc<Mutex<HashMap<Snowflake, Vec<Role>>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref COMMAND_CACHE: Arc<Mutex<HashMap<Snowflake, Vec<ApplicationCommand>>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref PROFILE_CACHE: Arc<Mutex<HashMap<ProfileKey, UserProfile>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref STICKER_CACHE: Arc<Mutex<HashMap<Snowflake, Vec<Sticker>>>> = Arc::new(Mutex::new(HashMap::new()));
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Message {
-    id: String,
+    id: Snowflake,
     content: String,
     attachments: Vec<MessageAttachment>,
     author: MessageAuthor,
-    channel_id: String,
+    channel_id: Snowflake,
+    /// Only sent for messages that did not come from a channel's history,
+    /// such as those in the mentions inbox.
+    #[serde(default)]
+    guild_id: Option<Snowflake>,
+    /// The bot behind an interaction response, which component clicks go to.
+    #[serde(default)]
+    application_id: Option<Snowflake>,
+    /// Set on a bot's response to a slash command.
+    #[serde(default)]
+    interaction: Option<MessageInteraction>,
     components: Vec<MessageComponent>,
     embeds: Vec<MessageEmbed>,
-    edited_timestamp: Option<String>,
-    flags: Option<u64>,
+    #[serde(default)]
+    sticker_items: Vec<StickerItem>,
+    /// The thread started on this message, if any.
+    #[serde(default)]
+    thread: Option<Thread>,
+    edited_timestamp: Option<DateTime<Utc>>,
+    flags: Option<MessageFlags>,
     mention_everyone: bool,
-    mention_roles: Vec<String>,
+    mention_roles: Vec<Snowflake>,
     mentions: Vec<MessageMention>,
     pinned: bool,
     reactions: Option<Vec<MessageReaction>>,
-    timestamp: String,
+    timestamp: DateTime<Utc>,
     tts: bool,
     #[serde(rename = "type")]
-    message_type: u64,
+    message_type: MessageType,
+}
+
+impl Message {
+    /// Whether this message pings `user_id`, directly, through one of
+    /// `member_roles`, or through @everyone/@here.
+    fn mentions_user(&self, user_id: Snowflake, member_roles: &[Snowflake]) -> bool {
+        self.mention_everyone
+            || self.mentions.iter().any(|m| m.id == user_id)
+            || self.mention_roles.iter().any(|r| member_roles.contains(r))
+    }
+
+    /// The line shown in place of `author: content` for system messages.
+    fn system_text(&self) -> Option<String> {
+        let author = &self.author.username;
+        let target = self
+            .mentions
+            .first()
+            .map(|m| m.username.as_str())
+            .unwrap_or("someone");
+        let text = match self.message_type {
+            MessageType::RecipientAdd => format!("{} added {} to the group.", author, target),
+            MessageType::RecipientRemove if self.mentions.iter().any(|m| m.id == self.author.id) => {
+                format!("{} left the group.", author)
+            }
+            MessageType::RecipientRemove => {
+                format!("{} removed {} from the group.", author, target)
+            }
+            MessageType::Call => format!("{} started a call.", author),
+            MessageType::ChannelNameChange => {
+                format!("{} changed the channel name: {}", author, self.content)
+            }
+            MessageType::ChannelIconChange => format!("{} changed the channel icon.", author),
+            MessageType::ChannelPinnedMessage => {
+                format!("{} pinned a message to this channel.", author)
+            }
+            MessageType::UserJoin => format!("{} joined the server.", author),
+            MessageType::GuildBoost => match self.content.parse::<u64>() {
+                Ok(count) if count > 1 => {
+                    format!("{} just boosted the server {} times!", author, count)
+                }
+                _ => format!("{} just boosted the server!", author),
+            },
+            MessageType::GuildBoostTier1 => format!(
+                "{} just boosted the server! The server has achieved Level 1!",
+                author
+            ),
+            MessageType::GuildBoostTier2 => format!(
+                "{} just boosted the server! The server has achieved Level 2!",
+                author
+            ),
+            MessageType::GuildBoostTier3 => format!(
+                "{} just boosted the server! The server has achieved Level 3!",
+                author
+            ),
+            MessageType::ChannelFollowAdd => format!(
+                "{} has added {} to this channel.",
+                author, self.content
+            ),
+            MessageType::GuildDiscoveryDisqualified => {
+                "This server has been removed from Server Discovery.".to_string()
+            }
+            MessageType::GuildDiscoveryRequalified => {
+                "This server is eligible for Server Discovery again.".to_string()
+            }
+            MessageType::GuildDiscoveryGracePeriodInitialWarning
+            | MessageType::GuildDiscoveryGracePeriodFinalWarning => {
+                "This server has failed Discovery activity requirements.".to_string()
+            }
+            MessageType::ThreadCreated => {
+                format!("{} started a thread: {}", author, self.content)
+            }
+            MessageType::GuildInviteReminder => {
+                "Wondering who to invite? Start by inviting anyone who can help you build the server!"
+                    .to_string()
+            }
+            MessageType::AutoModerationAction => "AutoMod has blocked a message.".to_string(),
+            _ => return None,
+        };
+        Some(text)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageInteraction {
+    name: String,
+    user: MessageAuthor,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct MessageDelete {
+    id: Snowflake,
+    channel_id: Snowflake,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct MessageAttachment {
-    id: String,
+    id: Snowflake,
     filename: String,
     content_type: Option<String>,
     size: u64,
@@ -54,29 +187,50 @@ struct MessageAttachment {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct MessageAuthor {
-    id: String,
+    id: Snowflake,
     username: String,
+    /// The display name chosen on the new username system.
+    #[serde(default)]
+    global_name: Option<String>,
     avatar: Option<String>,
     discriminator: String,
-    public_flags: u64,
+    #[serde(default)]
+    public_flags: UserFlags,
+    #[serde(default)]
+    bot: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct MessageComponent {
     #[serde(rename = "type")]
-    component_type: u64,
-    style: Option<u64>,
+    component_type: ComponentType,
+    style: Option<ButtonStyle>,
     label: Option<String>,
     emoji: Option<MessageEmoji>,
     custom_id: Option<String>,
     url: Option<String>,
     disabled: Option<bool>,
+    #[serde(default)]
+    options: Vec<SelectOption>,
+    placeholder: Option<String>,
+    min_values: Option<u64>,
+    max_values: Option<u64>,
     components: Option<Vec<MessageComponent>>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SelectOption {
+    label: String,
+    value: String,
+    description: Option<String>,
+    emoji: Option<MessageEmoji>,
+    #[serde(default)]
+    default: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct MessageEmoji {
-    id: Option<String>,
+    id: Option<Snowflake>,
     name: Option<String>,
     animated: Option<bool>,
 }
@@ -84,17 +238,93 @@ struct MessageEmoji {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct MessageEmbed {
     title: Option<String>,
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default = "default_embed_type")]
     embed_type: String,
     description: Option<String>,
     url: Option<String>,
-    timestamp: Option<String>,
+    timestamp: Option<DateTime<Utc>>,
     color: Option<u64>,
-    // footer: Option<MessageEmbedFooter>,
-    // image: Option<MessageEmbedImage>,
-    // thumbnail: Option<MessageEmbedThumbnail>,
-    // video: Option<MessageEmbedVideo>,
-    // provider: Option<MessageEmbedProvider>,
+    footer: Option<MessageEmbedFooter>,
+    image: Option<MessageEmbedMedia>,
+    thumbnail: Option<MessageEmbedMedia>,
+    video: Option<MessageEmbedMedia>,
+    provider: Option<MessageEmbedProvider>,
+    author: Option<MessageEmbedAuthor>,
+    #[serde(default)]
+    fields: Vec<MessageEmbedField>,
+}
+
+fn default_embed_type() -> String {
+    "rich".to_string()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageEmbedFooter {
+    text: String,
+    icon_url: Option<String>,
+    proxy_icon_url: Option<String>,
+}
+
+/// An embed's image, thumbnail or video, which share a shape.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageEmbedMedia {
+    url: String,
+    proxy_url: Option<String>,
+    height: Option<u64>,
+    width: Option<u64>,
+}
+
+impl MessageEmbedMedia {
+    /// Discord's media proxy serves resized copies, so prefer it.
+    fn source(&self) -> &str {
+        self.proxy_url.as_deref().unwrap_or(&self.url)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageEmbedProvider {
+    name: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageEmbedAuthor {
+    name: String,
+    url: Option<String>,
+    icon_url: Option<String>,
+    proxy_icon_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageEmbedField {
+    name: String,
+    value: String,
+    #[serde(default)]
+    inline: bool,
+}
+
+/// The part of a sticker that messages carry; enough to draw it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StickerItem {
+    id: Snowflake,
+    name: String,
+    format_type: StickerFormat,
+}
+
+/// A guild's own sticker, as listed in the sticker picker.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Sticker {
+    id: Snowflake,
+    name: String,
+    description: Option<String>,
+    format_type: StickerFormat,
+    /// False once the guild has lost the boost level the sticker needs.
+    #[serde(default = "default_available")]
+    available: bool,
+}
+
+fn default_available() -> bool {
+    true
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -106,51 +336,249 @@ struct MessageReaction {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct MessageMention {
-    id: String,
+    id: Snowflake,
     username: String,
     discriminator: String,
     avatar: Option<String>,
-    public_flags: u64,
+    public_flags: UserFlags,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Guild {
     features: Vec<String>,
     icon: Option<String>,
-    id: String,
+    id: Snowflake,
     name: String,
     owner: bool,
     permissions: u64,
-    permissions_new: String,
+    permissions_new: Permissions,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct GuildMember {
+    user: Option<MessageAuthor>,
+    nick: Option<String>,
+    roles: Vec<Snowflake>,
+    joined_at: DateTime<Utc>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Channel {
     flags: u64,
-    guild_id: Option<String>,
-    id: String,
+    guild_id: Option<Snowflake>,
+    id: Snowflake,
     name: String,
     nsfw: Option<bool>,
-    parent_id: Option<String>,
+    parent_id: Option<Snowflake>,
     permission_overwrites: Vec<PermissionOverwrite>,
     position: u64,
     rate_limit_per_user: Option<u64>,
     topic: Option<String>,
-    r#type: u64,
+    r#type: ChannelType,
+    /// For forums, the tags posts can carry.
+    #[serde(default)]
+    available_tags: Vec<ForumTag>,
+    default_sort_order: Option<u64>,
+}
+
+/// The parts of READY the client keeps; the gateway module takes the session
+/// id from it.
+#[derive(Clone, Debug, Deserialize)]
+struct ReadyEvent {
+    #[serde(default)]
+    read_state: ReadStatePayload,
+    #[serde(default)]
+    guilds: Vec<ReadyGuild>,
+    #[serde(default)]
+    private_channels: Vec<PrivateChannel>,
+    /// Users referred to by id elsewhere in READY.
+    #[serde(default)]
+    users: Vec<MessageAuthor>,
+    #[serde(default)]
+    presences: Vec<PresenceUpdate>,
+    /// User accounts get their friends' presences here instead.
+    #[serde(default)]
+    merged_presences: MergedPresences,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+struct MergedPresences {
+    #[serde(default)]
+    friends: Vec<PresenceUpdate>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TypingStart {
+    channel_id: Snowflake,
+    user_id: Snowflake,
+    /// Only sent in guilds.
+    member: Option<GuildMember>,
+}
+
+/// Someone who started typing in a channel, shown until they send a message
+/// or [`TYPING_TIMEOUT`] passes without them typing again.
+struct Typist {
+    channel_id: Snowflake,
+    user_id: Snowflake,
+    name: String,
+    since: Instant,
+}
+
+/// How long a TYPING_START lasts; clients repeat it while typing continues.
+const TYPING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often we tell Discord we are still typing.
+const TYPING_INTERVAL: Duration = Duration::from_secs(8);
+
+/// A user, and the guild whose membership details a profile includes.
+type ProfileKey = (Snowflake, Option<Snowflake>);
+
+/// Everything the profile popout shows, gathered from several endpoints.
+#[derive(Clone, Debug)]
+struct UserProfile {
+    user: MessageAuthor,
+    /// Their membership of the guild the popout was opened in, if any.
+    member: Option<GuildMember>,
+    /// `None` when Discord will not say, as it will not for bots.
+    mutual_guilds: Option<Vec<Snowflake>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ProfileResponse {
+    #[serde(default)]
+    mutual_guilds: Vec<MutualGuild>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct MutualGuild {
+    id: Snowflake,
+}
+
+/// A DM or group DM. These have none of a guild channel's layout or
+/// permissions, just the people in them.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct PermissionOverwrite {
-    allow: u64,
-    deny: u64,
-    id: String,
-    r#type: String,
+struct PrivateChannel {
+    id: Snowflake,
+    #[serde(rename = "type")]
+    channel_type: ChannelType,
+    /// Only set on group DMs someone has named.
+    name: Option<String>,
+    icon: Option<String>,
+    last_message_id: Option<Snowflake>,
+    /// Everyone in the conversation except us.
+    #[serde(default)]
+    recipients: Vec<MessageAuthor>,
+    /// READY lists recipients by id, with the users sent alongside.
+    #[serde(default)]
+    recipient_ids: Vec<Snowflake>,
+}
+
+impl PrivateChannel {
+    fn display_name(&self) -> String {
+        if let Some(name) = self.name.as_ref().filter(|n| !n.is_empty()) {
+            return name.clone();
+        }
+        if self.recipients.is_empty() {
+            return "Unnamed conversation".to_string();
+        }
+        self.recipients
+            .iter()
+            .map(|r| r.username.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The thread open in the side panel, next to its parent channel.
+struct OpenThread {
+    thread: Thread,
+    messages: Promise<Result<Vec<Message>, String>>,
+    /// The thread's own composer.
+    message: String,
+    /// An archive or unarchive in flight.
+    archive_request: Option<Promise<Result<Thread, String>>>,
+    error: Option<String>,
+}
+
+/// The thread browser for a channel: archived threads are paged in on
+/// demand, newest first.
+struct ThreadBrowser {
+    channel_id: Snowflake,
+    archived: Vec<Thread>,
+    has_more: bool,
+    page: Option<Promise<Result<ThreadList, String>>>,
+    error: Option<String>,
+}
+
+/// A forum's posts, shown in place of messages. Active posts come from the
+/// guild's threads; archived ones are paged in here.
+struct ForumView {
+    channel_id: Snowflake,
+    archived: Vec<Thread>,
+    has_more: bool,
+    page: Option<Promise<Result<ThreadList, String>>>,
+    error: Option<String>,
+    sort: SortOrder,
+    tag: Option<Snowflake>,
+    draft: Option<PostDraft>,
+}
+
+#[derive(Default)]
+struct PostDraft {
+    title: String,
+    body: String,
+    tags: Vec<Snowflake>,
+    request: Option<Promise<Result<Thread, String>>>,
+}
+
+/// The "Create Thread" window for a message.
+struct ThreadDraft {
+    channel_id: Snowflake,
+    message_id: Snowflake,
+    name: String,
+    auto_archive_duration: u64,
+    request: Option<Promise<Result<Thread, String>>>,
+}
+
+/// The pinned messages of the channel the pins popover was opened in.
+struct Pins {
+    channel_id: Snowflake,
+    messages: Promise<Result<Vec<Message>, String>>,
+}
+
+/// How long we wait for the bot to answer a component click before giving
+/// up on it.
+const INTERACTION_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A component interaction waiting on the bot. `request` is the POST, taken
+/// once it succeeds; after that we wait for the bot's answer to arrive over
+/// the gateway.
+struct PendingInteraction {
+    channel_id: Snowflake,
+    application_id: Snowflake,
+    nonce: Snowflake,
+    request: Option<Promise<Result<(), String>>>,
+    sent: Instant,
+}
+
+impl PendingInteraction {
+    /// Whether `message` is the bot's reply, or its edit of the clicked
+    /// message, rather than someone else talking in the channel.
+    fn answered_by(&self, message: &Message) -> bool {
+        message.channel_id == self.channel_id
+            && (message.application_id == Some(self.application_id)
+                || message.author.id == self.application_id
+                || message.interaction.is_some())
+    }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Default, Deserialize, Serialize)]
 #[serde(default)]
 struct DiscordClient {
     token: Option<String>,
+    settings: Settings,
+    #[serde(skip)]
+    settings_open: bool,
     // #[serde(skip)]
     // client: Option<discord::Client>,
     #[serde(skip)]
@@ -160,28 +588,112 @@ struct DiscordClient {
     #[serde(skip)]
     channels: Option<Promise<Result<Vec<Channel>, String>>>,
     #[serde(skip)]
-    selected_guild: Option<String>,
+    selected_guild: Option<Snowflake>,
     #[serde(skip)]
-    selected_channel: Option<String>,
+    selected_channel: Option<Snowflake>,
     #[serde(skip)]
     message: String,
     #[serde(skip)]
     messages: Option<Promise<Result<Vec<Message>, String>>>,
-}
-
-impl Default for DiscordClient {
-    fn default() -> Self {
-        Self {
-            token: None,
-            guilds: None,
-            gateway: None,
-            channels: None,
-            selected_guild: None,
-            selected_channel: None,
-            message: String::new(),
-            messages: None,
-        }
-    }
+    #[serde(skip)]
+    permissions: Option<Promise<Result<GuildPermissions, String>>>,
+    #[serde(skip)]
+    roles: Option<Promise<Result<Vec<Role>, String>>>,
+    #[serde(skip)]
+    user_popout: Option<Snowflake>,
+    #[serde(skip)]
+    profile: Option<Promise<Result<UserProfile, String>>>,
+    #[serde(skip)]
+    lightbox: Option<String>,
+    #[serde(skip)]
+    interaction: Option<PendingInteraction>,
+    #[serde(skip)]
+    interaction_error: Option<String>,
+    /// Slash commands available in the selected guild, fetched the first time
+    /// the composer starts with `/`.
+    #[serde(skip)]
+    commands: Option<Promise<Result<Vec<ApplicationCommand>, String>>>,
+    #[serde(skip)]
+    command_request: Option<Promise<Result<(), String>>>,
+    /// The selected guild's stickers, fetched when the picker is first opened.
+    #[serde(skip)]
+    stickers: Option<Promise<Result<Vec<Sticker>, String>>>,
+    #[serde(skip)]
+    sticker_picker_open: bool,
+    #[serde(skip)]
+    typing: Vec<Typist>,
+    #[serde(skip)]
+    read_states: ReadStates,
+    /// Whether the channel panel lists DMs instead of a guild's channels.
+    #[serde(skip)]
+    direct_messages_open: bool,
+    #[serde(skip)]
+    private_channels: Option<Promise<Result<Vec<PrivateChannel>, String>>>,
+    /// The user id typed into the "new message" field.
+    #[serde(skip)]
+    new_dm: String,
+    #[serde(skip)]
+    dm_request: Option<Promise<Result<PrivateChannel, String>>>,
+    members_hidden: bool,
+    #[serde(skip)]
+    member_list: MemberList,
+    /// Members received so far from op 8, which bots use instead of the
+    /// lazy list. `members_stale` marks that the list needs rebuilding from
+    /// them once the guild's roles are in.
+    #[serde(skip)]
+    member_chunks: Vec<Member>,
+    #[serde(skip)]
+    members_stale: bool,
+    /// The guild, channel and block of rows our op 14 subscription covers.
+    #[serde(skip)]
+    member_subscription: Option<(Snowflake, Snowflake, usize)>,
+    /// Where and when we last sent a typing notification, to throttle them.
+    #[serde(skip)]
+    typing_sent: Option<(Snowflake, Instant)>,
+    #[serde(skip)]
+    current_user: Option<Promise<Result<MessageAuthor, String>>>,
+    #[serde(skip)]
+    mention_inbox: Option<Promise<Result<Vec<Message>, String>>>,
+    #[serde(skip)]
+    mentions_open: bool,
+    /// Newest inbox mention already seen in each guild, so the badge only
+    /// counts ones that arrived since the guild was last opened. Saved, so
+    /// that a restart does not count the whole inbox again.
+    mentions_seen: HashMap<Snowflake, Snowflake>,
+    /// When the user last touched the keyboard or mouse, for auto-idle.
+    #[serde(skip)]
+    last_input: Option<Instant>,
+    /// Other users' statuses and activities.
+    #[serde(skip)]
+    presences: Presences,
+    /// The presence the gateway last heard from us this session.
+    #[serde(skip)]
+    presence_sent: Option<Presence>,
+    /// The selected guild's active threads.
+    #[serde(skip)]
+    threads: Option<Promise<Result<Threads, String>>>,
+    #[serde(skip)]
+    open_thread: Option<OpenThread>,
+    #[serde(skip)]
+    thread_browser: Option<ThreadBrowser>,
+    #[serde(skip)]
+    thread_draft: Option<ThreadDraft>,
+    /// The selected channel's posts, when it is a forum.
+    #[serde(skip)]
+    forum: Option<ForumView>,
+    #[serde(skip)]
+    pins: Option<Pins>,
+    /// A message to bring into view once the open channel draws it.
+    #[serde(skip)]
+    scroll_to_message: Option<Snowflake>,
+    /// Whether the open channel shows the messages around one jumped to
+    /// rather than its latest ones.
+    #[serde(skip)]
+    viewing_history: bool,
+    /// Whether the last jump's message was not among those loaded around
+    /// it, such as when it has been deleted.
+    #[serde(skip)]
+    jump_missed: bool,
 }
 
 impl App for DiscordClient {
@@ -214,12 +726,36 @@ impl App for DiscordClient {
 
             self.gateway.get_or_insert_with(|| {
                 let t = token.clone();
+                let waker = ctx.clone();
+                discord::gateway::set_waker(move || waker.request_repaint());
                 Promise::spawn_async(async move {
                     let mut client = GatewayClient::new(t);
-                    client.start().await;
-                    Ok(client)
+                    client.start().await.map(|()| client)
+                })
+            });
+
+            self.handle_gateway_events();
+            self.expire_typing(ctx);
+            self.update_presence(ctx);
+            self.acknowledge_selected(token);
+
+            self.current_user.get_or_insert_with(|| {
+                let t = token.clone();
+                Promise::spawn_async(async move {
+                    discord::api::get::<MessageAuthor>(&t, "/users/@me", "user").await
+                })
+            });
+
+            self.mention_inbox
+                .get_or_insert_with(|| fetch_mention_inbox(token.clone()));
+
+            self.private_channels.get_or_insert_with(|| {
+                let t = token.clone();
+                Promise::spawn_async(async move {
+                    discord::api::get::<Vec<PrivateChannel>>(&t, "/users/@me/channels", "DMs").await
                 })
             });
+            self.poll_dm_request(token);
 
             self.guilds.get_or_insert_with(|| {
                 let t = token.clone();
@@ -251,20 +787,117 @@ impl App for DiscordClient {
                         if ui.button("Log out").clicked() {
                             self.token = None;
                             self.guilds = None;
+                            discord::gateway::shutdown();
                             self.gateway = None;
                             self.channels = None;
                             self.selected_guild = None;
                             self.selected_channel = None;
                             self.message = String::new();
+                            self.messages = None;
+                            self.permissions = None;
+                            self.roles = None;
+                            self.user_popout = None;
+                            self.profile = None;
+                            self.lightbox = None;
+                            self.interaction = None;
+                            self.interaction_error = None;
+                            self.commands = None;
+                            self.command_request = None;
+                            self.stickers = None;
+                            self.sticker_picker_open = false;
+                            self.typing.clear();
+                            self.typing_sent = None;
+                            self.read_states = ReadStates::default();
+                            self.direct_messages_open = false;
+                            self.private_channels = None;
+                            self.new_dm.clear();
+                            self.dm_request = None;
+                            self.member_list = MemberList::default();
+                            self.member_chunks.clear();
+                            self.members_stale = false;
+                            self.member_subscription = None;
+                            self.current_user = None;
+                            self.mention_inbox = None;
+                            self.mentions_open = false;
+                            self.mentions_seen.clear();
+                            self.last_input = None;
+                            self.presence_sent = None;
+                            self.presences = Presences::default();
+                            self.threads = None;
+                            self.open_thread = None;
+                            self.thread_browser = None;
+                            self.thread_draft = None;
+                            self.forum = None;
+                            self.pins = None;
+                            self.scroll_to_message = None;
+                            self.viewing_history = false;
+                            self.jump_missed = false;
                             ctx.request_repaint();
                         }
+                        if ui.button("Mentions").clicked() {
+                            self.mentions_open = !self.mentions_open;
+                            ui.close_menu();
+                        }
+                        if ui.button("Members").clicked() {
+                            self.members_hidden = !self.members_hidden;
+                            ui.close_menu();
+                        }
+                        if ui.button("Settings").clicked() {
+                            self.settings_open = true;
+                            ui.close_menu();
+                        }
                         if ui.button("Quit").clicked() {
                             frame.close();
                         }
                     });
+                    ui::presence::status_menu(ui, &mut self.settings);
                 });
             });
 
+            ui::settings::settings_window(ctx, &mut self.settings_open, &mut self.settings);
+
+            let mut actions = Vec::new();
+            let mut jump = None;
+            if self.mentions_open {
+                egui::SidePanel::right("mentions_panel").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading("Mentions");
+                        if ui.small_button("⟳").on_hover_text("Refresh").clicked() {
+                            self.mention_inbox = Some(fetch_mention_inbox(token.clone()));
+                        }
+                    });
+                    ui.separator();
+                    match self.mention_inbox.as_ref().and_then(|p| p.ready()) {
+                        Some(Ok(messages)) => {
+                            let mut cx = RenderContext {
+                                channels: ready_or_empty(&self.channels),
+                                roles: ready_or_empty(&self.roles),
+                                user_id: self.current_user_id(),
+                                member_roles: &[],
+                                threads: false,
+                                can_pin: false,
+                                scroll_to: None,
+                                actions: Vec::new(),
+                            };
+                            jump = ui::mentions::inbox(
+                                ui,
+                                messages,
+                                ready_or_empty(&self.guilds),
+                                self.selected_guild,
+                                &mut cx,
+                            );
+                            actions = cx.actions;
+                        }
+                        Some(Err(err)) => {
+                            ui.label(err);
+                        }
+                        None => {
+                            ui.spinner();
+                        }
+                    }
+                });
+            }
+
             egui::SidePanel::left("side_panel").show(ctx, |ui| {
                 ui.heading("Welcome to Discord Client");
 
@@ -273,7 +906,7 @@ impl App for DiscordClient {
                     // ui.text_edit_singleline(&mut "a");
                     if let Some(gateway) = &self.gateway {
                         if let Some(gateway) = gateway.ready() {
-                            if let Ok(gateway) = gateway {
+                            if gateway.is_ok() {
                                 ui.label("Connected to gateway");
                             } else if let Err(err) = gateway {
                                 ui.label(err);
@@ -281,62 +914,55 @@ impl App for DiscordClient {
                         }
                     }
                 });
+                if let Some(warning) = discord::gateway::warning() {
+                    ui.label(egui::RichText::new(warning).small().weak());
+                }
+                let mut opened = None;
+                let mut mark_read = None;
+                if ui
+                    .selectable_label(self.direct_messages_open, "Direct Messages")
+                    .clicked()
+                    && !self.direct_messages_open
+                {
+                    self.open_direct_messages();
+                }
                 ScrollArea::vertical().show(ui, |ui| {
                     if let Some(guilds) = &self.guilds {
                         if let Some(guilds) = guilds.ready() {
                             if let Ok(guilds) = guilds {
                                 for guild in guilds {
                                     // ui.label(guild.name.clone());
-                                    let label = ui.selectable_label(
-                                        self.selected_guild.as_ref().unwrap_or(&"".to_string())
-                                            == &guild.id,
-                                        guild.name.clone(),
-                                    );
-                                    if label.clicked()
-                                        && (self.selected_guild.as_ref().unwrap_or(&"".to_string())
-                                            != &guild.id)
-                                    {
-                                        self.selected_guild = Some(guild.id.clone());
-                                        let t = token.clone();
-                                        let id = guild.id.clone();
-                                        self.channels = Some(Promise::spawn_async(async move {
-                                            let mut cache = CHANNEL_CACHE.lock().await;
-                                            if let Some(channels) = cache.get(&id) {
-                                                println!("{:?}", channels.clone());
-                                                Ok(channels.clone())
-                                            } else {
-                                                let request = reqwest::Client::new()
-                                                    .get(format!(
-                                                        "https://discord.com/api/guilds/{}/channels",
-                                                        &id
-                                                    ))
-                                                    .header("Authorization", t)
-                                                    .send()
-                                                    .await;
-                                                match request {
-                                                    Ok(response) => {
-                                                        let channels =
-                                                            response.json::<Vec<Channel>>().await;
-                                                        match channels {
-                                                            Ok(channels) => {
-                                                                cache.insert(
-                                                                    id.clone(),
-                                                                    channels.clone(),
-                                                                );
-                                                                println!("{:?}", channels.clone());
-                                                                Ok(channels)
-                                                            }
-                                                            Err(err) => Err(format!(
-                                                                "Failed to parse channels: {}",
-                                                                err
-                                                            )),
-                                                        }
-                                                    }
-                                                    Err(err) => Err(err.to_string()),
-                                                }
+                                    ui.horizontal(|ui| {
+                                        let unread = self.read_states.guild_unread(guild.id);
+                                        ui::guilds::unread_pip(ui, unread);
+                                        let icon = ui::guilds::icon(ui, guild);
+                                        let mut name = egui::RichText::new(&guild.name);
+                                        if unread {
+                                            name = name.strong();
+                                        }
+                                        let label = ui.selectable_label(
+                                            self.selected_guild == Some(guild.id),
+                                            name,
+                                        );
+                                        if (icon.clicked() || label.clicked())
+                                            && self.selected_guild != Some(guild.id)
+                                        {
+                                            opened = Some(guild.clone());
+                                        }
+                                        label.context_menu(|ui| {
+                                            if ui
+                                                .add_enabled(
+                                                    unread,
+                                                    egui::Button::new("Mark as read"),
+                                                )
+                                                .clicked()
+                                            {
+                                                mark_read = Some(guild.id);
+                                                ui.close_menu();
                                             }
-                                        }));
-                                    }
+                                        });
+                                        ui::mentions::badge(ui, self.unseen_mentions(guild.id));
+                                    });
                                 }
                             } else if let Err(err) = guilds {
                                 ui.label(err);
@@ -344,6 +970,12 @@ impl App for DiscordClient {
                         }
                     }
                 });
+                if let Some(guild) = opened {
+                    self.open_guild(token, &guild);
+                }
+                if let Some(guild_id) = mark_read {
+                    self.mark_guild_read(token, guild_id);
+                }
 
                 // ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 //     ui.horizontal(|ui| {
@@ -360,58 +992,76 @@ impl App for DiscordClient {
             });
 
             egui::SidePanel::left("side_panel_channels").show(ctx, |ui| {
+                if self.direct_messages_open {
+                    self.direct_messages_panel(ui, token);
+                    return;
+                }
                 ui.heading("Channels");
+                let mut mark_read = None;
+                let mut open_thread = None;
+                let mut selected = None;
                 ScrollArea::vertical().show(ui, |ui| {
                     if let Some(channels) = &self.channels {
                         if let Some(channels) = channels.ready() {
                             if let Ok(channels) = channels {
-                                for channel in channels {
+                                for channel in sort_channels(channels) {
+                                    if let Some(permissions) = self.channel_permissions(channel) {
+                                        if !permissions.contains(Permissions::VIEW_CHANNEL) {
+                                            continue;
+                                        }
+                                    }
+                                    if channel.r#type == ChannelType::GuildCategory {
+                                        ui.add_space(4.0);
+                                        ui.label(
+                                            egui::RichText::new(channel.name.to_uppercase())
+                                                .small()
+                                                .strong(),
+                                        );
+                                        continue;
+                                    }
+                                    if !channel.r#type.is_text()
+                                        && channel.r#type != ChannelType::GuildForum
+                                    {
+                                        ui.add_enabled(
+                                            false,
+                                            egui::SelectableLabel::new(false, channel.name.clone()),
+                                        );
+                                        continue;
+                                    }
+                                    let unread = self.read_states.is_unread(channel.id);
+                                    let mut name = egui::RichText::new(&channel.name);
+                                    if unread {
+                                        name = name.strong();
+                                    }
                                     let label = ui.selectable_label(
-                                        self.selected_channel.as_ref().unwrap_or(&"".to_string())
-                                            == &channel.id,
-                                        channel.name.clone(),
+                                        self.selected_channel == Some(channel.id),
+                                        name,
                                     );
                                     if label.clicked() {
-                                        self.selected_channel = Some(channel.id.clone());
-                                        let t = token.clone();
-                                        let id = channel.id.clone();
-                                        self.messages = Some(Promise::spawn_async(async move {
-                                            let mut cache = MESSAGE_CACHE.lock().await;
-                                            if let Some(messages) = cache.get(&id) {
-                                                println!("{:?}", messages.clone());
-                                                Ok(messages.clone())
-                                            } else {
-                                                let request = reqwest::Client::new()
-                                                    .get(format!(
-                                                        "https://discord.com/api/channels/{}/messages",
-                                                        &id
-                                                    ))
-                                                    .header("Authorization", t)
-                                                    .send()
-                                                    .await;
-                                                match request {
-                                                    Ok(response) => {
-                                                        let messages =
-                                                            response.json::<Vec<Message>>().await;
-                                                        match messages {
-                                                            Ok(messages) => {
-                                                                cache.insert(
-                                                                    id.clone(),
-                                                                    messages.clone(),
-                                                                );
-                                                                println!("{:?}", messages.clone());
-                                                                Ok(messages)
-                                                            }
-                                                            Err(err) => Err(format!(
-                                                                "Failed to parse messages: {}",
-                                                                err
-                                                            )),
-                                                        }
-                                                    }
-                                                    Err(err) => Err(err.to_string()),
-                                                }
-                                            }
-                                        }));
+                                        selected = Some(channel.id);
+                                    }
+                                    label.context_menu(|ui| {
+                                        if ui
+                                            .add_enabled(unread, egui::Button::new("Mark as read"))
+                                            .clicked()
+                                        {
+                                            mark_read = Some(channel.id);
+                                            ui.close_menu();
+                                        }
+                                    });
+                                    if let Some(Ok(threads)) =
+                                        self.threads.as_ref().and_then(|p| p.ready())
+                                    {
+                                        let clicked = ui::threads::list(
+                                            ui,
+                                            threads,
+                                            channel.id,
+                                            self.open_thread.as_ref().map(|t| t.thread.id),
+                                            &self.read_states,
+                                        );
+                                        if clicked.is_some() {
+                                            open_thread = clicked;
+                                        }
                                     }
                                 }
                             } else if let Err(err) = channels {
@@ -420,38 +1070,287 @@ impl App for DiscordClient {
                         }
                     }
                 });
+                if let Some(channel_id) = mark_read {
+                    self.mark_channel_read(token, channel_id);
+                }
+                if let Some(channel_id) = selected {
+                    self.select_channel(token, channel_id);
+                    if self
+                        .open_thread
+                        .as_ref()
+                        .is_some_and(|t| t.thread.parent_id != Some(channel_id))
+                    {
+                        self.open_thread = None;
+                    }
+                }
+                if let Some(thread) = open_thread.and_then(|id| self.find_thread(id)) {
+                    self.open_thread(token, thread);
+                }
             });
 
+            if self.open_thread.is_some() {
+                actions.extend(self.thread_panel(ctx, token));
+            }
+
+            if self.selected_guild.is_some() && !self.members_hidden {
+                self.rebuild_member_list();
+                let (clicked, visible) = egui::SidePanel::right("members_panel")
+                    .show(ctx, |ui| {
+                        ui.heading("Members");
+                        ui::members::panel(
+                            ui,
+                            &self.member_list,
+                            ready_or_empty(&self.roles),
+                            &self.presences,
+                        )
+                    })
+                    .inner;
+                self.sync_member_list(visible.start);
+                if let Some(user_id) = clicked {
+                    actions.push(Action::OpenUser(user_id));
+                }
+            } else if self.selected_guild.is_some() && !self.is_bot() {
+                // The same subscription brings THREAD_LIST_SYNC, which is how
+                // user accounts learn of active threads, so it is kept up
+                // with the panel hidden too.
+                let row = self
+                    .member_subscription
+                    .map_or(0, |(_, _, block)| block * discord::members::RANGE_SIZE);
+                self.sync_member_list(row);
+            }
+
             egui::CentralPanel::default().show(ctx, |ui| {
                 ui.heading("Discord Client");
                 // ui.add(egui::TextEdit::singleline(token));
                 // ui.add(egui::Button::new("Log in").on_hover_text("Log in to Discord"));
-                if let Some(selected_channel) = &self.selected_channel {
-                    ScrollArea::vertical().show(ui, |ui| {
-                        if let Some(messages) = &self.messages {
-                            if let Some(messages) = messages.ready() {
-                                if let Ok(messages) = messages {
-                                    for message in messages {
-                                        ui.label(format!(
-                                            "{}: {}",
-                                            message.author.username, message.content
-                                        ));
-                                    }
-                                } else if let Err(err) = messages {
+                if let Some(selected_channel) = self.selected_channel {
+                    if self.forum.is_some() {
+                        self.forum_view(ui, token);
+                        return;
+                    }
+                    // Threads belong to guild text channels.
+                    let in_guild = self.selected_guild.is_some();
+                    let can_create_threads = in_guild
+                        && self
+                            .selected_channel_permissions()
+                            .is_none_or(|p| p.contains(Permissions::CREATE_PUBLIC_THREADS));
+                    // Outside guilds there are no permissions to check.
+                    let can_pin = self
+                        .selected_channel_permissions()
+                        .is_none_or(|p| p.contains(Permissions::MANAGE_MESSAGES));
+                    let mut pin_jump = None;
+                    let mut to_present = false;
+                    ui.horizontal(|ui| {
+                        if in_guild && ui.button("Threads").clicked() {
+                            self.thread_browser = match &self.thread_browser {
+                                Some(browser) if browser.channel_id == selected_channel => None,
+                                _ => Some(ThreadBrowser {
+                                    channel_id: selected_channel,
+                                    archived: Vec::new(),
+                                    has_more: false,
+                                    page: Some(fetch_archived_threads(
+                                        token.clone(),
+                                        selected_channel,
+                                        None,
+                                    )),
+                                    error: None,
+                                }),
+                            };
+                        }
+                        let pins_button = ui.button("📌 Pins");
+                        let popup_id = ui.make_persistent_id("pins_popup");
+                        if pins_button.clicked() {
+                            ui.memory().toggle_popup(popup_id);
+                        }
+                        egui::popup::popup_below_widget(ui, popup_id, &pins_button, |ui| {
+                            // Fetched on opening, and again after a pin changes.
+                            if self.pins.as_ref().map(|p| p.channel_id) != Some(selected_channel) {
+                                self.pins = Some(Pins {
+                                    channel_id: selected_channel,
+                                    messages: fetch_pins(token.clone(), selected_channel),
+                                });
+                            }
+                            match self.pins.as_ref().and_then(|p| p.messages.ready()) {
+                                Some(Ok(messages)) => {
+                                    let mut cx = RenderContext {
+                                        channels: ready_or_empty(&self.channels),
+                                        roles: ready_or_empty(&self.roles),
+                                        user_id: self.current_user_id(),
+                                        member_roles: self.member_roles(),
+                                        threads: false,
+                                        can_pin: false,
+                                        scroll_to: None,
+                                        actions: Vec::new(),
+                                    };
+                                    pin_jump = ui::pins::pins(ui, messages, &mut cx);
+                                    actions.extend(cx.actions);
+                                }
+                                Some(Err(err)) => {
                                     ui.label(err);
                                 }
+                                None => {
+                                    ui.spinner();
+                                }
                             }
+                        });
+                        if pin_jump.is_some() {
+                            ui.memory().close_popup();
+                        }
+                        if self.jump_missed {
+                            ui.label(egui::RichText::new("Message not found").weak());
+                        }
+                        if self.viewing_history {
+                            ui.label(egui::RichText::new("Viewing older messages").weak());
+                            to_present = ui.button("Jump to present").clicked();
                         }
                     });
+                    if to_present {
+                        self.messages = Some(fetch_messages(token.clone(), selected_channel));
+                        self.scroll_to_message = None;
+                        self.viewing_history = false;
+                        self.jump_missed = false;
+                    }
+                    if let Some(message_id) = pin_jump {
+                        self.jump_to_message(token, selected_channel, message_id);
+                    }
+                    // Sticking to the bottom would undo a jump in the frame it
+                    // happens. Once the jump has moved the view up, egui only
+                    // sticks again when the user scrolls back down.
+                    let stick = self.scroll_to_message.is_none();
+                    ScrollArea::vertical()
+                        .stick_to_bottom(stick)
+                        .show(ui, |ui| {
+                            if let Some(messages) = &self.messages {
+                                if let Some(messages) = messages.ready() {
+                                    if let Ok(messages) = messages {
+                                        let mut cx = RenderContext {
+                                            channels: ready_or_empty(&self.channels),
+                                            roles: ready_or_empty(&self.roles),
+                                            user_id: self.current_user_id(),
+                                            member_roles: self.member_roles(),
+                                            threads: can_create_threads,
+                                            can_pin,
+                                            scroll_to: self.scroll_to_message,
+                                            actions: Vec::new(),
+                                        };
+                                        ui::messages::message_list(
+                                            ui,
+                                            messages,
+                                            &self.settings,
+                                            &mut cx,
+                                        );
+                                        let scrolled = cx.scroll_to.is_none();
+                                        actions.extend(cx.actions);
+                                        let missed = cx
+                                            .scroll_to
+                                            .is_some_and(|id| !messages.iter().any(|m| m.id == id));
+                                        if scrolled || missed {
+                                            self.scroll_to_message = None;
+                                        }
+                                        if missed {
+                                            self.jump_missed = true;
+                                        }
+                                    } else if let Err(err) = messages {
+                                        ui.label(err);
+                                        self.scroll_to_message = None;
+                                    }
+                                }
+                            }
+                            if to_present {
+                                // Back at the bottom, so the latest messages
+                                // stick there once they load.
+                                ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
+                            }
+                        });
+                    if self.interaction.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Waiting for the bot to respond…");
+                        });
+                    }
+                    if let Some(err) = &self.interaction_error {
+                        ui.colored_label(egui::Color32::from_rgb(237, 66, 69), err);
+                    }
+                    if self.message.starts_with('/') {
+                        if let Some(guild_id) = self.selected_guild {
+                            let commands = self
+                                .commands
+                                .get_or_insert_with(|| fetch_commands(token.clone(), guild_id));
+                            match commands.ready() {
+                                Some(Ok(commands)) => {
+                                    if let Some(text) =
+                                        ui::commands::suggestions(ui, &self.message, commands)
+                                    {
+                                        self.message = text;
+                                    }
+                                }
+                                Some(Err(err)) => {
+                                    ui.label(err);
+                                }
+                                None => {
+                                    ui.spinner();
+                                }
+                            }
+                        }
+                    }
+                    let can_send = self
+                        .selected_channel_permissions()
+                        .is_none_or(|p| p.contains(Permissions::SEND_MESSAGES));
+                    if self.sticker_picker_open && can_send {
+                        if let Some(guild_id) = self.selected_guild {
+                            let stickers = self
+                                .stickers
+                                .get_or_insert_with(|| fetch_stickers(token.clone(), guild_id));
+                            match stickers.ready() {
+                                Some(Ok(stickers)) => {
+                                    if let Some(sticker_id) = ui::stickers::picker(ui, stickers) {
+                                        send_sticker(token.clone(), selected_channel, sticker_id);
+                                        self.sticker_picker_open = false;
+                                    }
+                                }
+                                Some(Err(err)) => {
+                                    ui.label(err);
+                                }
+                                None => {
+                                    ui.spinner();
+                                }
+                            }
+                        }
+                    }
                     // Display text box for messages at the bottom of the panel
-                    ui.add(egui::TextEdit::multiline(&mut self.message));
-                    // Display button to send message
-                    if ui
-                        .add(egui::Button::new("Send message"))
-                        .clicked()
+                    let composer = ui.add_enabled(
+                        can_send,
+                        egui::TextEdit::multiline(&mut self.message).hint_text(if can_send {
+                            ""
+                        } else {
+                            "You do not have permission to send messages in this channel"
+                        }),
+                    );
+                    // Bots do not see typing, so commands do not send it.
+                    if composer.changed()
+                        && !self.message.is_empty()
+                        && !self.message.starts_with('/')
                     {
+                        self.send_typing(token, selected_channel);
+                    }
+                    // Display button to send message
+                    let (send_clicked, sticker_clicked) = ui
+                        .horizontal(|ui| {
+                            let send = ui.add_enabled(can_send, egui::Button::new("Send message"));
+                            // Only guilds have stickers of their own to pick from.
+                            let sticker = ui.add_enabled(
+                                can_send && self.selected_guild.is_some(),
+                                egui::SelectableLabel::new(self.sticker_picker_open, "Stickers"),
+                            );
+                            (send.clicked(), sticker.clicked())
+                        })
+                        .inner;
+                    if sticker_clicked {
+                        self.sticker_picker_open = !self.sticker_picker_open;
+                    }
+                    if send_clicked && !self.run_command(token, selected_channel) {
                         let t = token.clone();
-                        let s = selected_channel.clone();
+                        let s = selected_channel;
                         let m = self.message.clone();
                         let _ = Promise::spawn_async(async move {
                             let request = reqwest::Client::new()
@@ -477,9 +1376,33 @@ impl App for DiscordClient {
                             }
                         });
                         self.message = String::new();
+                        self.typing_sent = None;
                     }
+                    let typing: Vec<&str> = self
+                        .typing
+                        .iter()
+                        .filter(|t| t.channel_id == selected_channel)
+                        .map(|t| t.name.as_str())
+                        .collect();
+                    ui::typing::indicator(ui, &typing);
                 }
             });
+            for action in actions {
+                self.handle_action(token, action);
+            }
+            self.poll_interaction();
+            self.poll_threads(token);
+            if let Some((guild_id, channel_id)) = jump {
+                self.jump_to(token, guild_id, channel_id);
+            }
+            self.user_popout_window(ctx, token);
+            self.thread_browser_window(ctx, token);
+            self.thread_draft_window(ctx, token);
+            if let Some(url) = &self.lightbox {
+                if !ui::attachments::lightbox(ctx, url) {
+                    self.lightbox = None;
+                }
+            }
         } else {
             egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
                 egui::menu::bar(ui, |ui| {
@@ -493,6 +1416,7 @@ impl App for DiscordClient {
             egui::CentralPanel::default().show(ctx, |ui| {
                 ui.heading("Discord Client");
                 ui.label("Please enter your token");
+                ui.label(egui::RichText::new("For a bot, prefix its token with \"Bot \".").weak());
                 let mut token = String::new();
                 let text = ui.add(egui::TextEdit::singleline(&mut token));
                 if text.changed() {
@@ -513,6 +1437,1752 @@ impl DiscordClient {
         }
         Default::default()
     }
+
+    /// Our effective permissions in `channel`, or `None` while they are still
+    /// loading (or failed to load), in which case callers should not restrict
+    /// anything.
+    fn channel_permissions(&self, channel: &Channel) -> Option<Permissions> {
+        match self.permissions.as_ref()?.ready()? {
+            Ok(permissions) => Some(permissions.for_channel(&channel.permission_overwrites)),
+            Err(_) => None,
+        }
+    }
+
+    fn open_guild(&mut self, token: &str, guild: &Guild) {
+        self.direct_messages_open = false;
+        self.selected_guild = Some(guild.id);
+        self.selected_channel = None;
+        self.messages = None;
+        self.commands = None;
+        self.stickers = None;
+        self.sticker_picker_open = false;
+        self.permissions = Some(fetch_guild_permissions(token.to_string(), guild));
+        self.roles = Some(fetch_guild_roles(token.to_string(), guild.id));
+        self.channels = Some(fetch_channels(token.to_string(), guild.id));
+        // User accounts may not list active threads; THREAD_LIST_SYNC fills
+        // them in instead, and needs a store to land in straight away.
+        self.threads = Some(if self.is_bot() {
+            fetch_threads(token.to_string(), guild.id)
+        } else {
+            Promise::from_ready(Ok(Threads::new(guild.id, ThreadList::default())))
+        });
+        self.open_thread = None;
+        self.thread_browser = None;
+        self.thread_draft = None;
+        self.forum = None;
+        self.pins = None;
+        self.scroll_to_message = None;
+        self.viewing_history = false;
+        self.jump_missed = false;
+        if let Some(newest) = ready_or_empty(&self.mention_inbox)
+            .iter()
+            .filter(|m| m.guild_id == Some(guild.id))
+            .map(|m| m.id)
+            .max()
+        {
+            self.mentions_seen.insert(guild.id, newest);
+        }
+    }
+
+    /// Switches the channel panel to the DM list, leaving whatever guild was
+    /// open.
+    fn open_direct_messages(&mut self) {
+        self.direct_messages_open = true;
+        self.selected_guild = None;
+        self.selected_channel = None;
+        self.messages = None;
+        self.channels = None;
+        self.permissions = None;
+        self.roles = None;
+        self.commands = None;
+        self.stickers = None;
+        self.sticker_picker_open = false;
+        self.threads = None;
+        self.open_thread = None;
+        self.thread_browser = None;
+        self.thread_draft = None;
+        self.forum = None;
+        self.pins = None;
+        self.scroll_to_message = None;
+        self.viewing_history = false;
+        self.jump_missed = false;
+    }
+
+    fn open_private_channel(&mut self, token: &str, channel_id: Snowflake) {
+        if !self.direct_messages_open {
+            self.open_direct_messages();
+        }
+        if self.selected_channel != Some(channel_id) {
+            self.selected_channel = Some(channel_id);
+            self.messages = Some(fetch_messages(token.to_string(), channel_id));
+            self.scroll_to_message = None;
+            self.viewing_history = false;
+            self.jump_missed = false;
+        }
+    }
+
+    /// Opens our DM with `user_id`, creating it if we have never talked.
+    fn open_dm(&mut self, token: &str, user_id: Snowflake) {
+        let existing = ready_or_empty(&self.private_channels)
+            .iter()
+            .find(|c| {
+                c.channel_type == ChannelType::Dm
+                    && c.recipients.first().map(|r| r.id) == Some(user_id)
+            })
+            .map(|c| c.id);
+        if let Some(channel_id) = existing {
+            self.open_private_channel(token, channel_id);
+            return;
+        }
+        let token = token.to_string();
+        self.dm_request = Some(Promise::spawn_async(async move {
+            discord::api::create::<_, PrivateChannel>(
+                &token,
+                "/users/@me/channels",
+                &serde_json::json!({ "recipient_id": user_id }),
+                "DM",
+            )
+            .await
+        }));
+    }
+
+    fn poll_dm_request(&mut self, token: &str) {
+        let request = match self.dm_request.take() {
+            Some(request) => request,
+            None => return,
+        };
+        match request.try_take() {
+            Ok(Ok(channel)) => {
+                let channel_id = channel.id;
+                self.upsert_private_channel(channel);
+                self.new_dm.clear();
+                self.open_private_channel(token, channel_id);
+            }
+            // Failures stay put so the panel can show them.
+            Ok(Err(err)) => self.dm_request = Some(Promise::from_ready(Err(err))),
+            Err(request) => self.dm_request = Some(request),
+        }
+    }
+
+    fn upsert_private_channel(&mut self, channel: PrivateChannel) {
+        if let Some(Ok(channels)) = self.private_channels.as_mut().and_then(|p| p.ready_mut()) {
+            match channels.iter_mut().find(|c| c.id == channel.id) {
+                Some(existing) => *existing = channel,
+                None => channels.push(channel),
+            }
+        }
+    }
+
+    fn direct_messages_panel(&mut self, ui: &mut egui::Ui, token: &str) {
+        ui.heading("Direct Messages");
+        if let Some(user_id) = ui::direct_messages::new_message(ui, &mut self.new_dm) {
+            self.open_dm(token, user_id);
+        }
+        match self.dm_request.as_ref().map(|p| p.ready()) {
+            Some(Some(Err(err))) => {
+                ui.colored_label(egui::Color32::from_rgb(237, 66, 69), err);
+            }
+            Some(None) => {
+                ui.spinner();
+            }
+            _ => {}
+        }
+        ui.separator();
+        let mut opened = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            match self.private_channels.as_ref().and_then(|p| p.ready()) {
+                Some(Ok(channels)) => {
+                    opened = ui::direct_messages::list(
+                        ui,
+                        channels,
+                        self.selected_channel,
+                        &self.read_states,
+                        &self.presences,
+                    );
+                }
+                Some(Err(err)) => {
+                    ui.label(err);
+                }
+                None => {
+                    ui.spinner();
+                }
+            }
+        });
+        if let Some(channel_id) = opened {
+            self.open_private_channel(token, channel_id);
+        }
+    }
+
+    /// Records a created or changed thread wherever it is shown.
+    fn update_thread(&mut self, thread: Thread) {
+        if let Some(open) = &mut self.open_thread {
+            if open.thread.id == thread.id {
+                open.thread = thread.clone();
+            }
+        }
+        if let Some(browser) = &mut self.thread_browser {
+            browser.archived.retain(|t| t.id != thread.id);
+            if thread.is_archived() && thread.parent_id == Some(browser.channel_id) {
+                browser.archived.insert(0, thread.clone());
+            }
+        }
+        if let Some(forum) = &mut self.forum {
+            forum.archived.retain(|t| t.id != thread.id);
+            if thread.is_archived() && thread.parent_id == Some(forum.channel_id) {
+                forum.archived.insert(0, thread.clone());
+            }
+        }
+        if let Some(Ok(threads)) = self.threads.as_mut().and_then(|p| p.ready_mut()) {
+            threads.upsert(thread);
+        }
+    }
+
+    /// A thread we know of: an active one, one in the browser, or one
+    /// started on a message of the open channel.
+    fn find_thread(&self, thread_id: Snowflake) -> Option<Thread> {
+        let active = match self.threads.as_ref().and_then(|p| p.ready()) {
+            Some(Ok(threads)) => threads.get(thread_id).cloned(),
+            _ => None,
+        };
+        active
+            .or_else(|| {
+                let browser = self.thread_browser.as_ref()?;
+                browser.archived.iter().find(|t| t.id == thread_id).cloned()
+            })
+            .or_else(|| {
+                let forum = self.forum.as_ref()?;
+                forum.archived.iter().find(|t| t.id == thread_id).cloned()
+            })
+            .or_else(|| {
+                ready_or_empty(&self.messages)
+                    .iter()
+                    .filter_map(|m| m.thread.as_ref())
+                    .find(|t| t.id == thread_id)
+                    .cloned()
+            })
+    }
+
+    /// Opens a thread in the side panel, with its parent channel in the
+    /// middle.
+    fn open_thread(&mut self, token: &str, thread: Thread) {
+        if let Some(parent_id) = thread.parent_id {
+            if self.selected_channel != Some(parent_id) {
+                self.select_channel(token, parent_id);
+            }
+        }
+        if self.open_thread.as_ref().map(|t| t.thread.id) == Some(thread.id) {
+            return;
+        }
+        self.open_thread = Some(OpenThread {
+            messages: fetch_messages(token.to_string(), thread.id),
+            thread,
+            message: String::new(),
+            archive_request: None,
+            error: None,
+        });
+    }
+
+    /// Threads take their permissions from the channel they belong to.
+    fn thread_permissions(&self, thread: &Thread) -> Option<Permissions> {
+        let parent_id = thread.parent_id?;
+        let parent = ready_or_empty(&self.channels)
+            .iter()
+            .find(|c| c.id == parent_id)?;
+        self.channel_permissions(parent)
+    }
+
+    /// Picks up finished thread requests: a new thread or forum post, an
+    /// archive toggle or a page of archived threads.
+    fn poll_threads(&mut self, token: &str) {
+        let mut created = None;
+        if let Some(draft) = &mut self.thread_draft {
+            if let Some(request) = draft.request.take() {
+                match request.try_take() {
+                    Ok(Ok(thread)) => created = Some(thread),
+                    // Failures stay put so the window can show them.
+                    Ok(Err(err)) => draft.request = Some(Promise::from_ready(Err(err))),
+                    Err(request) => draft.request = Some(request),
+                }
+            }
+        }
+        if let Some(thread) = created {
+            self.thread_draft = None;
+            self.update_thread(thread.clone());
+            self.open_thread(token, thread);
+        }
+
+        let mut changed = None;
+        if let Some(open) = &mut self.open_thread {
+            if let Some(request) = open.archive_request.take() {
+                match request.try_take() {
+                    Ok(Ok(thread)) => changed = Some(thread),
+                    Ok(Err(err)) => open.error = Some(err),
+                    Err(request) => open.archive_request = Some(request),
+                }
+            }
+        }
+        if let Some(thread) = changed {
+            self.update_thread(thread);
+        }
+
+        let mut posted = None;
+        if let Some(forum) = &mut self.forum {
+            if let Some(page) = forum.page.take() {
+                match page.try_take() {
+                    Ok(Ok(list)) => {
+                        forum.has_more = list.has_more;
+                        forum.archived.extend(list.threads);
+                    }
+                    Ok(Err(err)) => forum.error = Some(err),
+                    Err(page) => forum.page = Some(page),
+                }
+            }
+            if let Some(draft) = &mut forum.draft {
+                if let Some(request) = draft.request.take() {
+                    match request.try_take() {
+                        Ok(Ok(thread)) => posted = Some(thread),
+                        Ok(Err(err)) => draft.request = Some(Promise::from_ready(Err(err))),
+                        Err(request) => draft.request = Some(request),
+                    }
+                }
+            }
+        }
+        if let Some(thread) = posted {
+            if let Some(forum) = &mut self.forum {
+                forum.draft = None;
+            }
+            self.update_thread(thread.clone());
+            self.open_thread(token, thread);
+        }
+
+        if let Some(browser) = &mut self.thread_browser {
+            if let Some(page) = browser.page.take() {
+                match page.try_take() {
+                    Ok(Ok(list)) => {
+                        browser.has_more = list.has_more;
+                        browser.archived.extend(list.threads);
+                    }
+                    Ok(Err(err)) => browser.error = Some(err),
+                    Err(page) => browser.page = Some(page),
+                }
+            }
+        }
+    }
+
+    /// The open thread's messages and composer, to the right of its parent
+    /// channel. Returns the actions clicked inside its messages.
+    fn thread_panel(&mut self, ctx: &egui::Context, token: &str) -> Vec<Action> {
+        let mut actions = Vec::new();
+        let (permissions, thread_id) = match &self.open_thread {
+            Some(open) => (self.thread_permissions(&open.thread), open.thread.id),
+            None => return actions,
+        };
+        let user_id = self.current_user_id();
+        let member_roles = self.member_roles().to_vec();
+        let typing: Vec<&str> = self
+            .typing
+            .iter()
+            .filter(|t| t.channel_id == thread_id)
+            .map(|t| t.name.as_str())
+            .collect();
+        let open = match &mut self.open_thread {
+            Some(open) => open,
+            None => return actions,
+        };
+        let can_archive = permissions.is_none_or(|p| p.contains(Permissions::MANAGE_THREADS))
+            || (user_id.is_some() && open.thread.owner_id == user_id);
+        // Sending in an archived thread unarchives it, unless it is locked.
+        let can_pin = permissions.is_none_or(|p| p.contains(Permissions::MANAGE_MESSAGES));
+        let can_send = !open.thread.is_locked()
+            && permissions.is_none_or(|p| p.contains(Permissions::SEND_MESSAGES_IN_THREADS));
+        let mut header_action = None;
+        let mut send = None;
+        let mut typed = false;
+        egui::SidePanel::right("thread_panel").show(ctx, |ui| {
+            header_action = ui::threads::header(ui, &open.thread, can_archive);
+            if let Some(err) = &open.error {
+                ui.colored_label(egui::Color32::from_rgb(237, 66, 69), err);
+            }
+            ui.separator();
+            ScrollArea::vertical()
+                .id_source("thread_messages")
+                .stick_to_bottom(true)
+                .max_height(ui.available_height() - 100.0)
+                .show(ui, |ui| match open.messages.ready() {
+                    Some(Ok(messages)) => {
+                        let mut cx = RenderContext {
+                            channels: ready_or_empty(&self.channels),
+                            roles: ready_or_empty(&self.roles),
+                            user_id,
+                            member_roles: &member_roles,
+                            threads: false,
+                            can_pin,
+                            scroll_to: None,
+                            actions: Vec::new(),
+                        };
+                        ui::messages::message_list(ui, messages, &self.settings, &mut cx);
+                        actions.extend(cx.actions);
+                    }
+                    Some(Err(err)) => {
+                        ui.label(err);
+                    }
+                    None => {
+                        ui.spinner();
+                    }
+                });
+            let composer = ui.add_enabled(
+                can_send,
+                egui::TextEdit::multiline(&mut open.message)
+                    .desired_rows(2)
+                    .hint_text(if can_send {
+                        "Message thread"
+                    } else {
+                        "You do not have permission to send messages in this thread"
+                    }),
+            );
+            typed = composer.changed() && !open.message.is_empty();
+            if ui
+                .add_enabled(
+                    can_send && !open.message.trim().is_empty(),
+                    egui::Button::new("Send message"),
+                )
+                .clicked()
+            {
+                send = Some(std::mem::take(&mut open.message));
+            }
+            ui::typing::indicator(ui, &typing);
+        });
+        match header_action {
+            Some(ui::threads::HeaderAction::Close) => self.open_thread = None,
+            Some(ui::threads::HeaderAction::SetArchived(archived)) => {
+                let token = token.to_string();
+                open.error = None;
+                open.archive_request = Some(Promise::spawn_async(async move {
+                    discord::threads::set_archived(&token, thread_id, archived).await
+                }));
+            }
+            None => {}
+        }
+        if typed {
+            self.send_typing(token, thread_id);
+        }
+        if let Some(content) = send {
+            send_message(token.to_string(), thread_id, content);
+            self.typing_sent = None;
+        }
+        actions
+    }
+
+    /// A forum's posts in place of messages, with sorting, a tag filter and
+    /// the form for a new post.
+    fn forum_view(&mut self, ui: &mut egui::Ui, token: &str) {
+        let channel = match self.forum.as_ref().and_then(|forum| {
+            ready_or_empty(&self.channels)
+                .iter()
+                .find(|c| c.id == forum.channel_id)
+        }) {
+            Some(channel) => channel.clone(),
+            None => return,
+        };
+        let permissions = self.channel_permissions(&channel);
+        let can_post = permissions.is_none_or(|p| p.contains(Permissions::SEND_MESSAGES));
+        let can_moderate = permissions.is_none_or(|p| p.contains(Permissions::MANAGE_THREADS));
+        let require_tag = channel.flags & discord::forum::REQUIRE_TAG != 0;
+        let forum = match &mut self.forum {
+            Some(forum) => forum,
+            None => return,
+        };
+
+        ui.heading(&channel.name);
+        if let Some(topic) = &channel.topic {
+            ui.label(egui::RichText::new(topic).weak());
+        }
+        if ui::forum::toolbar(
+            ui,
+            &channel.available_tags,
+            &mut forum.sort,
+            &mut forum.tag,
+            can_post,
+        ) && forum.draft.is_none()
+        {
+            forum.draft = Some(PostDraft::default());
+        }
+        let mut submit = false;
+        let mut cancel = false;
+        if let Some(draft) = &mut forum.draft {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                submit = ui::forum::post_form(
+                    ui,
+                    &channel.available_tags,
+                    &mut draft.title,
+                    &mut draft.body,
+                    &mut draft.tags,
+                    can_moderate,
+                    require_tag,
+                );
+                match draft.request.as_ref().map(|p| p.ready()) {
+                    Some(Some(Err(err))) => {
+                        ui.colored_label(egui::Color32::from_rgb(237, 66, 69), err);
+                    }
+                    Some(None) => {
+                        ui.spinner();
+                    }
+                    _ => {}
+                }
+                cancel = ui.button("Cancel").clicked();
+            });
+            let pending = matches!(draft.request.as_ref().map(|p| p.ready()), Some(None));
+            if submit && !pending {
+                let token = token.to_string();
+                let channel_id = channel.id;
+                let title = draft.title.trim().to_string();
+                let body = draft.body.clone();
+                let tags = draft.tags.clone();
+                draft.request = Some(Promise::spawn_async(async move {
+                    discord::forum::create_post(&token, channel_id, &title, &body, &tags).await
+                }));
+            }
+        }
+        if cancel {
+            forum.draft = None;
+        }
+        ui.separator();
+
+        let active = match self.threads.as_ref().and_then(|p| p.ready()) {
+            Some(Ok(threads)) => threads.under(channel.id),
+            _ => Vec::new(),
+        };
+        let archived = forum
+            .archived
+            .iter()
+            .filter(|t| !active.iter().any(|a| a.id == t.id));
+        let posts = discord::forum::arrange(
+            active.iter().copied().chain(archived),
+            forum.sort,
+            forum.tag,
+        );
+        let mut clicked = None;
+        let mut load_more = false;
+        ScrollArea::vertical().show(ui, |ui| {
+            if posts.is_empty() && forum.page.is_none() {
+                ui.label(egui::RichText::new("No posts yet").weak());
+            }
+            for post in posts {
+                if ui::forum::post(ui, post, &channel.available_tags) {
+                    clicked = Some(post.id);
+                }
+            }
+            if let Some(err) = &forum.error {
+                ui.label(err);
+            }
+            if forum.page.is_some() {
+                ui.spinner();
+            } else if forum.has_more && ui.button("Load more").clicked() {
+                load_more = true;
+            }
+        });
+        if load_more {
+            let before = forum
+                .archived
+                .last()
+                .and_then(|t| t.thread_metadata.as_ref())
+                .map(|m| m.archive_timestamp);
+            forum.page = Some(fetch_archived_threads(
+                token.to_string(),
+                channel.id,
+                before,
+            ));
+        }
+        if let Some(thread) = clicked.and_then(|id| self.find_thread(id)) {
+            self.open_thread(token, thread);
+        }
+    }
+
+    /// The active and archived threads of the channel the browser was
+    /// opened for.
+    fn thread_browser_window(&mut self, ctx: &egui::Context, token: &str) {
+        let browser = match &mut self.thread_browser {
+            Some(browser) => browser,
+            None => return,
+        };
+        let active = match self.threads.as_ref().and_then(|p| p.ready()) {
+            Some(Ok(threads)) => threads.under(browser.channel_id),
+            _ => Vec::new(),
+        };
+        let mut open = true;
+        let mut clicked = None;
+        let mut load_more = false;
+        egui::Window::new("Threads")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ScrollArea::vertical().show(ui, |ui| {
+                    clicked = ui::threads::browser(ui, &active, &browser.archived);
+                    if let Some(err) = &browser.error {
+                        ui.label(err);
+                    }
+                    if browser.page.is_some() {
+                        ui.spinner();
+                    } else if browser.has_more && ui.button("Load more").clicked() {
+                        load_more = true;
+                    }
+                });
+            });
+        if load_more {
+            let before = browser
+                .archived
+                .last()
+                .and_then(|t| t.thread_metadata.as_ref())
+                .map(|m| m.archive_timestamp);
+            browser.page = Some(fetch_archived_threads(
+                token.to_string(),
+                browser.channel_id,
+                before,
+            ));
+        }
+        if !open {
+            self.thread_browser = None;
+        }
+        if let Some(thread) = clicked.and_then(|id| self.find_thread(id)) {
+            self.open_thread(token, thread);
+        }
+    }
+
+    fn thread_draft_window(&mut self, ctx: &egui::Context, token: &str) {
+        let draft = match &mut self.thread_draft {
+            Some(draft) => draft,
+            None => return,
+        };
+        let mut open = true;
+        let mut create = false;
+        egui::Window::new("Create Thread")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                create =
+                    ui::threads::create_form(ui, &mut draft.name, &mut draft.auto_archive_duration);
+                match draft.request.as_ref().map(|p| p.ready()) {
+                    Some(Some(Err(err))) => {
+                        ui.colored_label(egui::Color32::from_rgb(237, 66, 69), err);
+                    }
+                    Some(None) => {
+                        ui.spinner();
+                    }
+                    _ => {}
+                }
+            });
+        let pending = matches!(draft.request.as_ref().map(|p| p.ready()), Some(None));
+        if create && !pending {
+            let token = token.to_string();
+            let (channel_id, message_id) = (draft.channel_id, draft.message_id);
+            let name = draft.name.trim().to_string();
+            let duration = draft.auto_archive_duration;
+            draft.request = Some(Promise::spawn_async(async move {
+                discord::threads::start_from_message(
+                    &token, channel_id, message_id, &name, duration,
+                )
+                .await
+            }));
+        }
+        if !open {
+            self.thread_draft = None;
+        }
+    }
+
+    /// Opens the channel a mention was sent in, switching guilds if needed.
+    fn jump_to(&mut self, token: &str, guild_id: Option<Snowflake>, channel_id: Snowflake) {
+        let private = ready_or_empty(&self.private_channels)
+            .iter()
+            .any(|c| c.id == channel_id);
+        if guild_id.is_none() && private {
+            self.open_private_channel(token, channel_id);
+            return;
+        }
+        if let Some(guild_id) = guild_id {
+            if self.selected_guild != Some(guild_id) {
+                let guild = ready_or_empty(&self.guilds)
+                    .iter()
+                    .find(|g| g.id == guild_id)
+                    .cloned();
+                if let Some(guild) = guild {
+                    self.open_guild(token, &guild);
+                }
+            }
+        }
+        if self.selected_channel != Some(channel_id) {
+            self.select_channel(token, channel_id);
+        }
+    }
+
+    /// Shows a guild channel in the middle: its messages, or for a forum,
+    /// its posts.
+    fn select_channel(&mut self, token: &str, channel_id: Snowflake) {
+        self.selected_channel = Some(channel_id);
+        self.scroll_to_message = None;
+        self.viewing_history = false;
+        self.jump_missed = false;
+        let forum = ready_or_empty(&self.channels)
+            .iter()
+            .find(|c| c.id == channel_id && c.r#type == ChannelType::GuildForum)
+            .map(|c| SortOrder::from_channel(c.default_sort_order));
+        match forum {
+            Some(sort) => {
+                self.messages = None;
+                self.forum = Some(ForumView {
+                    channel_id,
+                    archived: Vec::new(),
+                    has_more: false,
+                    page: Some(fetch_archived_threads(token.to_string(), channel_id, None)),
+                    error: None,
+                    sort,
+                    tag: None,
+                    draft: None,
+                });
+            }
+            None => {
+                self.forum = None;
+                self.messages = Some(fetch_messages(token.to_string(), channel_id));
+            }
+        }
+    }
+
+    /// Scrolls the open channel to a message, first loading the messages
+    /// around it if it is further back than what is loaded.
+    fn jump_to_message(&mut self, token: &str, channel_id: Snowflake, message_id: Snowflake) {
+        self.scroll_to_message = Some(message_id);
+        self.jump_missed = false;
+        let loaded = ready_or_empty(&self.messages)
+            .iter()
+            .any(|m| m.id == message_id);
+        if !loaded {
+            self.messages = Some(fetch_messages_around(
+                token.to_string(),
+                channel_id,
+                message_id,
+            ));
+            self.viewing_history = true;
+        }
+    }
+
+    fn unseen_mentions(&self, guild_id: Snowflake) -> usize {
+        let seen = self.mentions_seen.get(&guild_id).copied();
+        ready_or_empty(&self.mention_inbox)
+            .iter()
+            .filter(|m| m.guild_id == Some(guild_id) && seen.is_none_or(|seen| m.id > seen))
+            .count()
+    }
+
+    /// Runs the composer's text as a slash command if it names one of the
+    /// guild's commands. Returns `false` if it should be sent as a message.
+    fn run_command(&mut self, token: &str, channel_id: Snowflake) -> bool {
+        let (name, arguments) = match discord::commands::parse_invocation(&self.message) {
+            Some(invocation) => invocation,
+            None => return false,
+        };
+        let command = match ready_or_empty(&self.commands)
+            .iter()
+            .find(|c| c.name == name)
+        {
+            Some(command) => command.clone(),
+            None => return false,
+        };
+        let options = match discord::commands::validate(&command, arguments) {
+            Ok(options) => options,
+            Err(err) => {
+                self.interaction_error = Some(err);
+                return true;
+            }
+        };
+        let session_id = match discord::gateway::session_id() {
+            Some(session_id) => session_id,
+            None => {
+                self.interaction_error = Some("Not connected to the gateway yet".to_string());
+                return true;
+            }
+        };
+        let interaction = CommandInteraction::new(
+            &command,
+            options,
+            self.selected_guild,
+            channel_id,
+            session_id,
+        );
+        let token = token.to_string();
+        self.command_request = Some(Promise::spawn_async(async move {
+            interaction.send(&token).await
+        }));
+        self.interaction_error = None;
+        self.message.clear();
+        true
+    }
+
+    /// Applies message events from the gateway to the open channel and to
+    /// the message cache.
+    fn handle_gateway_events(&mut self) {
+        for event in discord::gateway::drain_events() {
+            match event.name.as_str() {
+                "MESSAGE_CREATE" | "MESSAGE_UPDATE" => {
+                    // Updates that only carry changed fields (such as embed
+                    // resolution) do not parse and are skipped.
+                    if let Ok(message) = serde_json::from_value::<Message>(event.data) {
+                        if event.name == "MESSAGE_CREATE" {
+                            self.typing.retain(|t| {
+                                t.channel_id != message.channel_id || t.user_id != message.author.id
+                            });
+                            // Our own messages are read by definition.
+                            if Some(message.author.id) == self.current_user_id() {
+                                self.read_states.read(message.channel_id, message.id);
+                            } else {
+                                self.read_states
+                                    .message_created(message.channel_id, message.id);
+                            }
+                            if let Some(Ok(threads)) =
+                                self.threads.as_mut().and_then(|p| p.ready_mut())
+                            {
+                                threads.message_created(message.channel_id, message.id);
+                            }
+                        }
+                        if self
+                            .interaction
+                            .as_ref()
+                            .is_some_and(|i| i.answered_by(&message))
+                        {
+                            self.interaction = None;
+                        }
+                        self.apply_message(message);
+                    }
+                }
+                "INTERACTION_SUCCESS" | "INTERACTION_FAILURE" => {
+                    if let Ok(result) = serde_json::from_value::<InteractionResult>(event.data) {
+                        let pending = self.interaction.as_ref().map(|i| i.nonce);
+                        if result.nonce.is_some() && result.nonce == pending {
+                            self.interaction = None;
+                            if event.name == "INTERACTION_FAILURE" {
+                                self.interaction_error =
+                                    Some("The bot did not respond".to_string());
+                            }
+                        }
+                    }
+                }
+                "READY" => {
+                    if let Ok(ready) = serde_json::from_value::<ReadyEvent>(event.data) {
+                        self.load_ready(ready);
+                    }
+                }
+                "CHANNEL_CREATE" => {
+                    if let Ok(channel) = serde_json::from_value::<PrivateChannel>(event.data) {
+                        if channel.channel_type.is_private() {
+                            self.upsert_private_channel(channel);
+                        }
+                    }
+                }
+                "GUILD_MEMBER_LIST_UPDATE" => {
+                    if let Ok(update) = serde_json::from_value::<MemberListUpdate>(event.data) {
+                        for member in update.members() {
+                            if let Some(presence) = &member.presence {
+                                self.presences.insert(member.user.id, presence.clone());
+                            }
+                        }
+                        if self.member_list.guild_id == Some(update.guild_id) {
+                            self.member_list.apply(update);
+                        }
+                    }
+                }
+                "GUILD_MEMBERS_CHUNK" => {
+                    if let Ok(chunk) = serde_json::from_value::<MembersChunk>(event.data) {
+                        for update in &chunk.presences {
+                            self.presences.apply(update.clone());
+                        }
+                        if self.member_list.guild_id == Some(chunk.guild_id) {
+                            for member in chunk.into_members() {
+                                self.member_chunks.retain(|m| m.user.id != member.user.id);
+                                self.member_chunks.push(member);
+                            }
+                            self.members_stale = true;
+                        }
+                    }
+                }
+                "THREAD_CREATE" | "THREAD_UPDATE" => {
+                    if let Ok(thread) = serde_json::from_value::<Thread>(event.data) {
+                        self.update_thread(thread);
+                    }
+                }
+                "THREAD_DELETE" => {
+                    if let Ok(deleted) = serde_json::from_value::<ThreadDelete>(event.data) {
+                        if let Some(Ok(threads)) = self.threads.as_mut().and_then(|p| p.ready_mut())
+                        {
+                            threads.remove(deleted.id);
+                        }
+                        if let Some(browser) = &mut self.thread_browser {
+                            browser.archived.retain(|t| t.id != deleted.id);
+                        }
+                        if let Some(forum) = &mut self.forum {
+                            forum.archived.retain(|t| t.id != deleted.id);
+                        }
+                        if self.open_thread.as_ref().map(|t| t.thread.id) == Some(deleted.id) {
+                            self.open_thread = None;
+                        }
+                    }
+                }
+                "THREAD_LIST_SYNC" => {
+                    if let Ok(sync) = serde_json::from_value::<ThreadListSync>(event.data) {
+                        if let Some(Ok(threads)) = self.threads.as_mut().and_then(|p| p.ready_mut())
+                        {
+                            threads.sync(sync);
+                        }
+                    }
+                }
+                "THREAD_MEMBERS_UPDATE" => {
+                    if let Ok(update) = serde_json::from_value::<ThreadMembersUpdate>(event.data) {
+                        let user_id = self.current_user_id();
+                        if let Some(open) = &mut self.open_thread {
+                            if open.thread.id == update.id {
+                                open.thread.member_count = update.member_count;
+                            }
+                        }
+                        if let Some(Ok(threads)) = self.threads.as_mut().and_then(|p| p.ready_mut())
+                        {
+                            threads.members_update(update, user_id);
+                        }
+                    }
+                }
+                "PRESENCE_UPDATE" => {
+                    if let Ok(update) = serde_json::from_value::<PresenceUpdate>(event.data) {
+                        self.presences.apply(update);
+                    }
+                }
+                "CHANNEL_PINS_UPDATE" => {
+                    if let Ok(update) = serde_json::from_value::<ChannelPinsUpdate>(event.data) {
+                        // Dropped so the popover fetches them afresh.
+                        if self.pins.as_ref().map(|p| p.channel_id) == Some(update.channel_id) {
+                            self.pins = None;
+                        }
+                    }
+                }
+                "MESSAGE_ACK" => {
+                    if let Ok(ack) = serde_json::from_value::<MessageAck>(event.data) {
+                        self.read_states.read(ack.channel_id, ack.message_id);
+                    }
+                }
+                "TYPING_START" => {
+                    if let Ok(typing) = serde_json::from_value::<TypingStart>(event.data) {
+                        self.start_typing(typing);
+                    }
+                }
+                "MESSAGE_DELETE" => {
+                    if let Ok(deleted) = serde_json::from_value::<MessageDelete>(event.data) {
+                        if self.selected_channel == Some(deleted.channel_id) {
+                            if let Some(Ok(messages)) =
+                                self.messages.as_mut().and_then(|p| p.ready_mut())
+                            {
+                                messages.retain(|m| m.id != deleted.id);
+                            }
+                        }
+                        async_std::task::spawn(async move {
+                            if let Some(messages) =
+                                MESSAGE_CACHE.lock().await.get_mut(&deleted.channel_id)
+                            {
+                                messages.retain(|m| m.id != deleted.id);
+                            }
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn load_ready(&mut self, ready: ReadyEvent) {
+        // A new session starts out online; tell it our status again.
+        self.presence_sent = None;
+        for update in ready
+            .presences
+            .into_iter()
+            .chain(ready.merged_presences.friends)
+        {
+            self.presences.apply(update);
+        }
+        self.read_states = ReadStates::load(&ready.read_state, &ready.guilds);
+        let mut channels = ready.private_channels;
+        for channel in &mut channels {
+            if channel.recipients.is_empty() {
+                channel.recipients = channel
+                    .recipient_ids
+                    .iter()
+                    .filter_map(|id| ready.users.iter().find(|u| u.id == *id).cloned())
+                    .collect();
+            }
+            if let Some(message_id) = channel.last_message_id {
+                self.read_states.message_created(channel.id, message_id);
+            }
+        }
+        // READY is as fresh as it gets, so it replaces the REST list.
+        self.private_channels = Some(Promise::from_ready(Ok(channels)));
+    }
+
+    /// Known from the token's "Bot " prefix before our user has loaded.
+    fn is_bot(&self) -> bool {
+        self.token
+            .as_deref()
+            .is_some_and(|token| token.starts_with("Bot "))
+            || matches!(
+                self.current_user.as_ref().and_then(|p| p.ready()),
+                Some(Ok(user)) if user.bot
+            )
+    }
+
+    /// Makes sure the gateway is sending the selected guild's member list,
+    /// and for user accounts, the rows around `visible_row`.
+    fn sync_member_list(&mut self, visible_row: usize) {
+        let guild_id = match self.selected_guild {
+            Some(guild_id) => guild_id,
+            None => return,
+        };
+        if self.is_bot() {
+            if self.member_list.guild_id != Some(guild_id) {
+                self.member_list = MemberList::new(guild_id);
+                self.member_chunks.clear();
+                discord::members::request_guild_members(guild_id);
+            }
+            return;
+        }
+        // The lazy list is per channel; without one open, use the first we
+        // can see.
+        let channel_id = match self.selected_channel.or_else(|| {
+            ready_or_empty(&self.channels)
+                .iter()
+                .find(|c| c.r#type.is_text())
+                .map(|c| c.id)
+        }) {
+            Some(channel_id) => channel_id,
+            None => return,
+        };
+        let block = visible_row / discord::members::RANGE_SIZE;
+        let subscription = Some((guild_id, channel_id, block));
+        if self.member_subscription == subscription {
+            return;
+        }
+        if self.member_subscription.map(|(g, c, _)| (g, c)) != Some((guild_id, channel_id)) {
+            self.member_list = MemberList::new(guild_id);
+        }
+        self.member_subscription = subscription;
+        discord::members::subscribe(guild_id, channel_id, visible_row);
+    }
+
+    /// Regroups the members op 8 has sent, once the roles to group them by
+    /// have loaded.
+    fn rebuild_member_list(&mut self) {
+        if !self.members_stale {
+            return;
+        }
+        let (guild_id, roles) = match (
+            self.member_list.guild_id,
+            self.roles.as_ref().and_then(|p| p.ready()),
+        ) {
+            (Some(guild_id), Some(Ok(roles))) => (guild_id, roles),
+            _ => return,
+        };
+        self.member_list = MemberList::from_members(guild_id, &self.member_chunks, roles);
+        self.members_stale = false;
+    }
+
+    /// Reads whatever has arrived in the open channel, acknowledging it to
+    /// the server when that moves our read state forward.
+    fn acknowledge_selected(&mut self, token: &str) {
+        let channel_id = match self.selected_channel {
+            Some(channel_id) => channel_id,
+            None => return,
+        };
+        let loaded = ready_or_empty(&self.messages).last().map(|m| m.id);
+        let newest = loaded.max(self.read_states.last_message(channel_id));
+        if let Some(message_id) = newest {
+            // Bots keep read state only locally; Discord rejects their acks.
+            if self.read_states.read(channel_id, message_id) && !self.is_bot() {
+                send_ack(token.to_string(), channel_id, message_id);
+            }
+        }
+    }
+
+    fn mark_channel_read(&mut self, token: &str, channel_id: Snowflake) {
+        if let Some(message_id) = self.read_states.last_message(channel_id) {
+            if self.read_states.read(channel_id, message_id) && !self.is_bot() {
+                send_ack(token.to_string(), channel_id, message_id);
+            }
+        }
+    }
+
+    fn mark_guild_read(&mut self, token: &str, guild_id: Snowflake) {
+        let reads = self.read_states.read_guild(guild_id);
+        if reads.is_empty() || self.is_bot() {
+            return;
+        }
+        let token = token.to_string();
+        async_std::task::spawn(async move {
+            if let Err(err) = discord::read_state::ack_bulk(&token, reads).await {
+                println!("Failed to mark guild as read: {}", err);
+            }
+        });
+    }
+
+    fn start_typing(&mut self, typing: TypingStart) {
+        if Some(typing.user_id) == self.current_user_id() {
+            return;
+        }
+        let member_name = typing.member.and_then(|member| {
+            member
+                .nick
+                .or_else(|| member.user.map(|user| user.username))
+        });
+        // Outside guilds, go by the names in the loaded messages.
+        let name = member_name
+            .or_else(|| {
+                ready_or_empty(&self.messages)
+                    .iter()
+                    .find(|m| m.author.id == typing.user_id)
+                    .map(|m| m.author.username.clone())
+            })
+            .unwrap_or_else(|| "Someone".to_string());
+        self.typing
+            .retain(|t| t.channel_id != typing.channel_id || t.user_id != typing.user_id);
+        self.typing.push(Typist {
+            channel_id: typing.channel_id,
+            user_id: typing.user_id,
+            name,
+            since: Instant::now(),
+        });
+    }
+
+    /// Works out the presence to show — going idle after a while without
+    /// input and dropping an expired custom status — and sends it when it
+    /// changes.
+    fn update_presence(&mut self, ctx: &egui::Context) {
+        let input = ctx.input();
+        if !input.events.is_empty() || input.pointer.is_moving() {
+            self.last_input = Some(Instant::now());
+        }
+        drop(input);
+        let last_input = *self.last_input.get_or_insert_with(Instant::now);
+
+        let now = Utc::now().timestamp_millis();
+        if let Some(custom_status) = &self.settings.custom_status {
+            if custom_status.is_expired(now) {
+                self.settings.custom_status = None;
+            } else if let Some(at) = custom_status.expires_at {
+                ctx.request_repaint_after(Duration::from_millis((at - now) as u64));
+            }
+        }
+
+        let mut presence = Presence {
+            status: self.settings.status,
+            custom_status: self.settings.custom_status.clone(),
+            afk_since: None,
+        };
+        if self.settings.status == Status::Online && self.settings.idle_after_minutes > 0 {
+            let idle_after = Duration::from_secs(self.settings.idle_after_minutes as u64 * 60);
+            let away = last_input.elapsed();
+            if away >= idle_after {
+                presence.status = Status::Idle;
+                presence.afk_since = Some(now - away.as_millis() as i64);
+            } else {
+                ctx.request_repaint_after(idle_after - away);
+            }
+        }
+        // Only the switch into idle matters, not the moment it is dated.
+        let changed = self.presence_sent.as_ref().is_none_or(|sent| {
+            sent.status != presence.status || sent.custom_status != presence.custom_status
+        });
+        if changed && discord::gateway::session_id().is_some() {
+            presence.send();
+            self.presence_sent = Some(presence);
+        }
+    }
+
+    /// Drops typists who have gone quiet, and schedules a repaint for when
+    /// the next one will.
+    fn expire_typing(&mut self, ctx: &egui::Context) {
+        self.typing.retain(|t| t.since.elapsed() < TYPING_TIMEOUT);
+        if let Some(oldest) = self.typing.iter().map(|t| t.since).min() {
+            ctx.request_repaint_after(TYPING_TIMEOUT.saturating_sub(oldest.elapsed()));
+        }
+    }
+
+    fn send_typing(&mut self, token: &str, channel_id: Snowflake) {
+        if self.typing_sent.is_some_and(|(channel, sent)| {
+            channel == channel_id && sent.elapsed() < TYPING_INTERVAL
+        }) {
+            return;
+        }
+        self.typing_sent = Some((channel_id, Instant::now()));
+        let token = token.to_string();
+        async_std::task::spawn(async move {
+            let path = format!("/channels/{}/typing", channel_id);
+            if let Err(err) = discord::api::post(&token, &path, &serde_json::json!({})).await {
+                println!("Failed to send typing: {}", err);
+            }
+        });
+    }
+
+    fn apply_message(&mut self, message: Message) {
+        if self.selected_channel == Some(message.channel_id) {
+            if let Some(Ok(messages)) = self.messages.as_mut().and_then(|p| p.ready_mut()) {
+                if !self.viewing_history {
+                    upsert_message(messages, message.clone());
+                } else if let Some(shown) = messages.iter_mut().find(|m| m.id == message.id) {
+                    // New messages belong after the page shown, not on it.
+                    *shown = message.clone();
+                }
+            }
+        }
+        if let Some(open) = &mut self.open_thread {
+            if open.thread.id == message.channel_id {
+                if let Some(Ok(messages)) = open.messages.ready_mut() {
+                    upsert_message(messages, message.clone());
+                }
+            }
+        }
+        // Ephemeral messages are gone once the channel is reopened.
+        let ephemeral = message
+            .flags
+            .is_some_and(|f| f.contains(MessageFlags::EPHEMERAL));
+        if !ephemeral {
+            async_std::task::spawn(async move {
+                if let Some(messages) = MESSAGE_CACHE.lock().await.get_mut(&message.channel_id) {
+                    upsert_message(messages, message);
+                }
+            });
+        }
+    }
+
+    /// Picks up the results of interaction requests. The bot's reply itself
+    /// arrives as a message event.
+    fn poll_interaction(&mut self) {
+        if let Some(request) = self.command_request.take() {
+            match request.try_take() {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => self.interaction_error = Some(format!("Command failed: {}", err)),
+                Err(request) => self.command_request = Some(request),
+            }
+        }
+        let pending = match &mut self.interaction {
+            Some(pending) => pending,
+            None => return,
+        };
+        if let Some(request) = pending.request.take() {
+            match request.try_take() {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => {
+                    self.interaction = None;
+                    self.interaction_error = Some(format!("Interaction failed: {}", err));
+                }
+                Err(request) => pending.request = Some(request),
+            }
+        }
+        if self
+            .interaction
+            .as_ref()
+            .is_some_and(|i| i.sent.elapsed() > INTERACTION_TIMEOUT)
+        {
+            self.interaction = None;
+            self.interaction_error = Some("The bot did not respond in time".to_string());
+        }
+    }
+
+    /// The guild a channel belongs to, if it is one we know of: the open
+    /// guild's channels and threads. DMs have none.
+    fn channel_guild(&self, channel_id: Snowflake) -> Option<Snowflake> {
+        if let Some(channel) = ready_or_empty(&self.channels)
+            .iter()
+            .find(|c| c.id == channel_id)
+        {
+            return channel.guild_id.or(self.selected_guild);
+        }
+        self.find_thread(channel_id)
+            .and_then(|thread| thread.guild_id)
+    }
+
+    fn current_user_id(&self) -> Option<Snowflake> {
+        match self.current_user.as_ref()?.ready()? {
+            Ok(user) => Some(user.id),
+            Err(_) => None,
+        }
+    }
+
+    /// Roles we hold in the selected guild, once its permissions have loaded.
+    fn member_roles(&self) -> &[Snowflake] {
+        match self.permissions.as_ref().and_then(|p| p.ready()) {
+            Some(Ok(permissions)) => &permissions.member_roles,
+            _ => &[],
+        }
+    }
+
+    fn handle_action(&mut self, token: &str, action: Action) {
+        match action {
+            Action::None => {}
+            Action::OpenChannel(id) => {
+                // Only channels of the open guild can be jumped to for now.
+                let channels = ready_or_empty(&self.channels);
+                let channel = match channels.iter().find(|c| c.id == id) {
+                    Some(channel)
+                        if channel.r#type.is_text()
+                            || channel.r#type == ChannelType::GuildForum =>
+                    {
+                        channel
+                    }
+                    _ => return,
+                };
+                let visible = self
+                    .channel_permissions(channel)
+                    .is_none_or(|p| p.contains(Permissions::VIEW_CHANNEL));
+                if visible && self.selected_channel != Some(id) {
+                    self.select_channel(token, id);
+                }
+            }
+            Action::OpenUser(id) => {
+                if self.user_popout != Some(id) {
+                    self.profile = None;
+                }
+                self.user_popout = Some(id);
+            }
+            Action::OpenImage(url) => self.lightbox = Some(url),
+            Action::OpenThread(id) => {
+                if let Some(thread) = self.find_thread(id) {
+                    self.open_thread(token, thread);
+                }
+            }
+            Action::Pin(channel_id, message_id) => {
+                set_pinned(token.to_string(), channel_id, message_id, true)
+            }
+            Action::Unpin(channel_id, message_id) => {
+                set_pinned(token.to_string(), channel_id, message_id, false)
+            }
+            Action::CreateThread(message_id) => {
+                let message = match ready_or_empty(&self.messages)
+                    .iter()
+                    .find(|m| m.id == message_id)
+                {
+                    Some(message) => message,
+                    None => return,
+                };
+                // Discord names the thread after the message to start with.
+                let mut name: String = message.content.chars().take(40).collect();
+                if name.trim().is_empty() {
+                    name = "New Thread".to_string();
+                }
+                self.thread_draft = Some(ThreadDraft {
+                    channel_id: message.channel_id,
+                    message_id,
+                    name,
+                    auto_archive_duration: 24 * 60,
+                    request: None,
+                });
+            }
+            Action::Interact(click) => {
+                let session_id = match discord::gateway::session_id() {
+                    Some(session_id) => session_id,
+                    None => {
+                        self.interaction_error =
+                            Some("Not connected to the gateway yet".to_string());
+                        return;
+                    }
+                };
+                let channel_id = click.channel_id;
+                let application_id = click.application_id;
+                let guild_id = click.guild_id.or_else(|| self.channel_guild(channel_id));
+                let interaction = ComponentInteraction::new(click, guild_id, session_id);
+                let nonce = interaction.nonce();
+                let token = token.to_string();
+                self.interaction_error = None;
+                self.interaction = Some(PendingInteraction {
+                    channel_id,
+                    application_id,
+                    nonce,
+                    request: Some(Promise::spawn_async(async move {
+                        interaction.send(&token).await
+                    })),
+                    sent: Instant::now(),
+                });
+            }
+        }
+    }
+
+    /// The profile of whoever was last clicked: a message author, a mention
+    /// or a member in the member list.
+    fn user_popout_window(&mut self, ctx: &egui::Context, token: &str) {
+        let id = match self.user_popout {
+            Some(id) => id,
+            None => return,
+        };
+        if self.profile.is_none() {
+            self.profile = Some(fetch_profile(token.to_string(), id, self.selected_guild));
+        }
+        let mut open = true;
+        let mut message = false;
+        egui::Window::new("User")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                match self.profile.as_ref().and_then(|p| p.ready()) {
+                    Some(Ok(profile)) => {
+                        message = ui::profile::profile(
+                            ui,
+                            profile,
+                            ready_or_empty(&self.guilds),
+                            ready_or_empty(&self.roles),
+                            self.presences.get(id),
+                        );
+                    }
+                    Some(Err(err)) => {
+                        ui.label(err);
+                    }
+                    None => {
+                        ui.spinner();
+                    }
+                }
+            });
+        if message {
+            self.open_dm(token, id);
+        }
+        if !open || message {
+            self.user_popout = None;
+            self.profile = None;
+        }
+    }
+
+    fn selected_channel_permissions(&self) -> Option<Permissions> {
+        let selected = self.selected_channel.as_ref()?;
+        let channels = self.channels.as_ref()?.ready()?.as_ref().ok()?;
+        let channel = channels.iter().find(|c| &c.id == selected)?;
+        self.channel_permissions(channel)
+    }
+}
+
+/// Orders channels the way Discord's sidebar does: uncategorized channels
+/// first, then each category followed by its children, each group sorted by
+/// position with text channels above voice channels. Channels whose category
+/// is missing from the list count as uncategorized.
+fn sort_channels(channels: &[Channel]) -> Vec<&Channel> {
+    let key = |c: &&Channel| (c.r#type.is_voice(), c.position, c.id);
+    let mut categories: Vec<&Channel> = channels
+        .iter()
+        .filter(|c| c.r#type == ChannelType::GuildCategory)
+        .collect();
+    categories.sort_by_key(key);
+    let mut top: Vec<&Channel> = channels
+        .iter()
+        .filter(|c| {
+            c.r#type != ChannelType::GuildCategory
+                && c.parent_id
+                    .is_none_or(|parent| !categories.iter().any(|category| category.id == parent))
+        })
+        .collect();
+    top.sort_by_key(key);
+
+    let mut sorted = top;
+    for category in categories {
+        sorted.push(category);
+        let mut children: Vec<&Channel> = channels
+            .iter()
+            .filter(|c| c.parent_id.as_ref() == Some(&category.id))
+            .collect();
+        children.sort_by_key(key);
+        sorted.extend(children);
+    }
+    sorted
+}
+
+fn fetch_guild_permissions(
+    token: String,
+    guild: &Guild,
+) -> Promise<Result<GuildPermissions, String>> {
+    let id = guild.id;
+    let owner = guild.owner;
+    Promise::spawn_async(async move {
+        let mut cache = PERMISSION_CACHE.lock().await;
+        if let Some(permissions) = cache.get(&id) {
+            return Ok(permissions.clone());
+        }
+        let roles = guild_roles(&token, id).await?;
+        let member = discord::api::get::<GuildMember>(
+            &token,
+            &format!("/users/@me/guilds/{}/member", id),
+            "member",
+        )
+        .await?;
+        let user_id = match member.user {
+            Some(user) => user.id,
+            None => return Err("Member response did not include a user".to_string()),
+        };
+        let permissions = GuildPermissions::new(id, user_id, owner, &roles, member.roles);
+        cache.insert(id, permissions.clone());
+        Ok(permissions)
+    })
+}
+
+fn fetch_channels(token: String, guild_id: Snowflake) -> Promise<Result<Vec<Channel>, String>> {
+    Promise::spawn_async(async move {
+        let mut cache = CHANNEL_CACHE.lock().await;
+        if let Some(channels) = cache.get(&guild_id) {
+            return Ok(channels.clone());
+        }
+        let channels = discord::api::get::<Vec<Channel>>(
+            &token,
+            &format!("/guilds/{}/channels", guild_id),
+            "channels",
+        )
+        .await?;
+        cache.insert(guild_id, channels.clone());
+        Ok(channels)
+    })
+}
+
+/// Recent messages that mention us across every guild, newest first.
+fn fetch_mention_inbox(token: String) -> Promise<Result<Vec<Message>, String>> {
+    Promise::spawn_async(async move {
+        discord::api::get::<Vec<Message>>(
+            &token,
+            "/users/@me/mentions?limit=50&roles=true&everyone=true",
+            "mentions",
+        )
+        .await
+    })
+}
+
+fn fetch_guild_roles(token: String, guild_id: Snowflake) -> Promise<Result<Vec<Role>, String>> {
+    Promise::spawn_async(async move { guild_roles(&token, guild_id).await })
+}
+
+async fn guild_roles(token: &str, guild_id: Snowflake) -> Result<Vec<Role>, String> {
+    if let Some(roles) = ROLE_CACHE.lock().await.get(&guild_id) {
+        return Ok(roles.clone());
+    }
+    let roles =
+        discord::api::get::<Vec<Role>>(token, &format!("/guilds/{}/roles", guild_id), "roles")
+            .await?;
+    ROLE_CACHE.lock().await.insert(guild_id, roles.clone());
+    Ok(roles)
+}
+
+fn fetch_messages(token: String, channel_id: Snowflake) -> Promise<Result<Vec<Message>, String>> {
+    Promise::spawn_async(async move { channel_messages(&token, channel_id).await })
+}
+
+async fn channel_messages(token: &str, channel_id: Snowflake) -> Result<Vec<Message>, String> {
+    let mut cache = MESSAGE_CACHE.lock().await;
+    if let Some(messages) = cache.get(&channel_id) {
+        return Ok(messages.clone());
+    }
+    let mut messages = discord::api::get::<Vec<Message>>(
+        token,
+        &format!("/channels/{}/messages", channel_id),
+        "messages",
+    )
+    .await?;
+    // Oldest first, so the newest message ends up at the bottom of the view.
+    messages.sort_by_key(|m| m.id);
+    cache.insert(channel_id, messages.clone());
+    Ok(messages)
+}
+
+/// The messages on either side of `message_id`, for jumping to one older
+/// than the channel's cached history. Not cached, as it is not the channel's
+/// latest page.
+fn fetch_messages_around(
+    token: String,
+    channel_id: Snowflake,
+    message_id: Snowflake,
+) -> Promise<Result<Vec<Message>, String>> {
+    Promise::spawn_async(async move {
+        let mut messages = discord::api::get::<Vec<Message>>(
+            &token,
+            &format!(
+                "/channels/{}/messages?around={}&limit=50",
+                channel_id, message_id
+            ),
+            "messages",
+        )
+        .await?;
+        messages.sort_by_key(|m| m.id);
+        Ok(messages)
+    })
+}
+
+/// The channel's pinned messages, most recently pinned first.
+fn fetch_pins(token: String, channel_id: Snowflake) -> Promise<Result<Vec<Message>, String>> {
+    Promise::spawn_async(async move {
+        discord::api::get(&token, &format!("/channels/{}/pins", channel_id), "pins").await
+    })
+}
+
+/// Replaces the message with the same id, or inserts it in id order.
+fn upsert_message(messages: &mut Vec<Message>, message: Message) {
+    match messages.binary_search_by_key(&message.id, |m| m.id) {
+        Ok(index) => messages[index] = message,
+        Err(index) => messages.insert(index, message),
+    }
+}
+
+fn fetch_commands(
+    token: String,
+    guild_id: Snowflake,
+) -> Promise<Result<Vec<ApplicationCommand>, String>> {
+    Promise::spawn_async(async move {
+        let mut cache = COMMAND_CACHE.lock().await;
+        if let Some(commands) = cache.get(&guild_id) {
+            return Ok(commands.clone());
+        }
+        let index = discord::api::get::<discord::commands::CommandIndex>(
+            &token,
+            &format!("/guilds/{}/application-command-index", guild_id),
+            "commands",
+        )
+        .await?;
+        cache.insert(guild_id, index.application_commands.clone());
+        Ok(index.application_commands)
+    })
+}
+
+/// Gathers a user's profile: the user, their membership of `guild_id` (if
+/// they are in it) and, where Discord allows, the guilds we share.
+fn fetch_profile(
+    token: String,
+    user_id: Snowflake,
+    guild_id: Option<Snowflake>,
+) -> Promise<Result<UserProfile, String>> {
+    Promise::spawn_async(async move {
+        if let Some(profile) = PROFILE_CACHE.lock().await.get(&(user_id, guild_id)) {
+            return Ok(profile.clone());
+        }
+        let user =
+            discord::api::get::<MessageAuthor>(&token, &format!("/users/{}", user_id), "user")
+                .await?;
+        let member = match guild_id {
+            Some(guild_id) => discord::api::get::<GuildMember>(
+                &token,
+                &format!("/guilds/{}/members/{}", guild_id, user_id),
+                "member",
+            )
+            .await
+            .ok(),
+            None => None,
+        };
+        let mutual_guilds = discord::api::get::<ProfileResponse>(
+            &token,
+            &format!("/users/{}/profile?with_mutual_guilds=true", user_id),
+            "profile",
+        )
+        .await
+        .ok()
+        .map(|profile| profile.mutual_guilds.into_iter().map(|g| g.id).collect());
+        let profile = UserProfile {
+            user,
+            member,
+            mutual_guilds,
+        };
+        PROFILE_CACHE
+            .lock()
+            .await
+            .insert((user_id, guild_id), profile.clone());
+        Ok(profile)
+    })
+}
+
+fn fetch_stickers(token: String, guild_id: Snowflake) -> Promise<Result<Vec<Sticker>, String>> {
+    Promise::spawn_async(async move {
+        let mut cache = STICKER_CACHE.lock().await;
+        if let Some(stickers) = cache.get(&guild_id) {
+            return Ok(stickers.clone());
+        }
+        let stickers = discord::api::get::<Vec<Sticker>>(
+            &token,
+            &format!("/guilds/{}/stickers", guild_id),
+            "stickers",
+        )
+        .await?;
+        cache.insert(guild_id, stickers.clone());
+        Ok(stickers)
+    })
+}
+
+fn send_ack(token: String, channel_id: Snowflake, message_id: Snowflake) {
+    async_std::task::spawn(async move {
+        if let Err(err) = discord::read_state::ack(&token, channel_id, message_id).await {
+            println!("Failed to acknowledge {}: {}", channel_id, err);
+        }
+    });
+}
+
+fn send_message(token: String, channel_id: Snowflake, content: String) {
+    async_std::task::spawn(async move {
+        let body = serde_json::json!({
+            "content": content,
+            "tts": false,
+            "nonce": Snowflake::from_time(std::time::SystemTime::now()),
+        });
+        let path = format!("/channels/{}/messages", channel_id);
+        if let Err(err) = discord::api::post(&token, &path, &body).await {
+            println!("Failed to send message: {}", err);
+        }
+    });
+}
+
+/// The message's pinned flag changes when the gateway sends its update.
+fn set_pinned(token: String, channel_id: Snowflake, message_id: Snowflake, pinned: bool) {
+    async_std::task::spawn(async move {
+        let result = if pinned {
+            discord::pins::pin(&token, channel_id, message_id).await
+        } else {
+            discord::pins::unpin(&token, channel_id, message_id).await
+        };
+        if let Err(err) = result {
+            println!("Failed to update pin: {}", err);
+        }
+    });
+}
+
+/// Posts a message made of just the sticker. It shows up in the channel when
+/// the gateway echoes it back.
+fn send_sticker(token: String, channel_id: Snowflake, sticker_id: Snowflake) {
+    async_std::task::spawn(async move {
+        let body = serde_json::json!({
+            "sticker_ids": [sticker_id],
+            "nonce": Snowflake::from_time(std::time::SystemTime::now()),
+        });
+        let path = format!("/channels/{}/messages", channel_id);
+        if let Err(err) = discord::api::post(&token, &path, &body).await {
+            println!("Failed to send sticker: {}", err);
+        }
+    });
+}
+
+/// The guild's active threads, for bots, which are not sent
+/// THREAD_LIST_SYNC. A failure just starts the list empty.
+fn fetch_threads(token: String, guild_id: Snowflake) -> Promise<Result<Threads, String>> {
+    Promise::spawn_async(async move {
+        let list = discord::threads::active(&token, guild_id)
+            .await
+            .unwrap_or_default();
+        Ok(Threads::new(guild_id, list))
+    })
+}
+
+fn fetch_archived_threads(
+    token: String,
+    channel_id: Snowflake,
+    before: Option<DateTime<Utc>>,
+) -> Promise<Result<ThreadList, String>> {
+    Promise::spawn_async(
+        async move { discord::threads::archived(&token, channel_id, before).await },
+    )
+}
+
+/// The loaded list behind `promise`, or nothing while it is pending or failed.
+fn ready_or_empty<T: Send>(promise: &Option<Promise<Result<Vec<T>, String>>>) -> &[T] {
+    match promise.as_ref().and_then(|p| p.ready()) {
+        Some(Ok(items)) => items,
+        _ => &[],
+    }
 }
 
 #[async_std::main]
@@ -0,0 +1,111 @@
+use egui::RichText;
+
+use super::{images, Action, RenderContext};
+use crate::MessageAttachment;
+
+const MAX_IMAGE_SIZE: egui::Vec2 = egui::vec2(400.0, 300.0);
+
+/// Image attachments are drawn inline at their declared size (scaled down),
+/// everything else as a card linking to the file.
+pub fn attachment(ui: &mut egui::Ui, attachment: &MessageAttachment, cx: &mut RenderContext) {
+    if is_image(attachment) {
+        let size = images::fit_size(attachment.width, attachment.height, MAX_IMAGE_SIZE);
+        let response = images::show(ui, &attachment.proxy_url, size);
+        if response.on_hover_text(&attachment.filename).clicked() {
+            cx.actions
+                .push(Action::OpenImage(attachment.proxy_url.clone()));
+        }
+    } else {
+        download_card(ui, attachment);
+    }
+}
+
+fn is_image(attachment: &MessageAttachment) -> bool {
+    match &attachment.content_type {
+        Some(content_type) => content_type.starts_with("image/"),
+        None => {
+            let name = attachment.filename.to_ascii_lowercase();
+            [".png", ".jpg", ".jpeg", ".gif", ".webp"]
+                .iter()
+                .any(|extension| name.ends_with(extension))
+        }
+    }
+}
+
+fn download_card(ui: &mut egui::Ui, attachment: &MessageAttachment) {
+    egui::Frame::none()
+        .fill(ui.visuals().faint_bg_color)
+        .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
+        .inner_margin(egui::style::Margin::same(8.0))
+        .rounding(4.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("📄").size(24.0));
+                ui.vertical(|ui| {
+                    ui.hyperlink_to(&attachment.filename, &attachment.url);
+                    ui.label(RichText::new(format_size(attachment.size)).small().weak());
+                });
+            });
+        });
+}
+
+/// "512 bytes", "1.50 KB", "12.34 MB": binary units, two decimal places.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", size, UNITS[unit])
+}
+
+/// The image at `url` over a darkened screen, scaled to fit. Returns `false`
+/// once the user dismisses it by clicking outside or pressing Escape.
+pub fn lightbox(ctx: &egui::Context, url: &str) -> bool {
+    let screen = ctx.input().screen_rect();
+    let mut open = !ctx.input().key_pressed(egui::Key::Escape);
+    egui::Area::new("lightbox")
+        .order(egui::Order::Foreground)
+        .fixed_pos(screen.min)
+        .show(ctx, |ui| {
+            let (rect, _) = ui.allocate_exact_size(screen.size(), egui::Sense::hover());
+            ui.painter()
+                .rect_filled(rect, 0.0, egui::Color32::from_black_alpha(200));
+            let max = screen.size() * 0.9;
+            let size = match images::texture(ctx, url) {
+                Some(texture) => {
+                    let size = texture.size_vec2();
+                    size * (max.x / size.x).min(max.y / size.y).min(1.0)
+                }
+                None => egui::vec2(64.0, 64.0),
+            };
+            let image_rect = egui::Rect::from_center_size(rect.center(), size);
+            let mut child = ui.child_ui(image_rect, egui::Layout::top_down(egui::Align::Min));
+            images::show(&mut child, url, size);
+            let link = egui::Rect::from_min_size(
+                egui::pos2(image_rect.min.x, image_rect.max.y + 4.0),
+                egui::vec2(size.x.max(120.0), 20.0),
+            );
+            let link = ui
+                .child_ui(link, egui::Layout::left_to_right(egui::Align::Center))
+                .hyperlink_to("Open original", url)
+                .rect;
+            // Sensed only now, after the image and link: egui hands a press
+            // to the first hovered widget that senses clicks, so theirs go to
+            // them. The click that opened the lightbox was pressed elsewhere
+            // and does not count either.
+            let backdrop = ui.interact(rect, ui.id().with("backdrop"), egui::Sense::click());
+            let outside = backdrop
+                .interact_pointer_pos()
+                .is_some_and(|pos| !image_rect.contains(pos) && !link.contains(pos));
+            if backdrop.clicked() && outside {
+                open = false;
+            }
+        });
+    open
+}
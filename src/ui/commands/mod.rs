@@ -0,0 +1,110 @@
+use egui::RichText;
+
+use crate::discord::commands::{self, ApplicationCommand};
+
+/// Most commands listed while the user is still typing a name.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Autocomplete for a composer `input` that starts with `/`: matching
+/// commands while the name is being typed, then the chosen command's options
+/// and the choices of the one being filled in. Returns the new composer text
+/// if a suggestion was clicked.
+pub fn suggestions(
+    ui: &mut egui::Ui,
+    input: &str,
+    commands: &[ApplicationCommand],
+) -> Option<String> {
+    let (name, rest) = commands::parse_invocation(input)?;
+    let typing_name = !input.contains(char::is_whitespace);
+    let mut replacement = None;
+    egui::Frame::none()
+        .fill(ui.visuals().faint_bg_color)
+        .inner_margin(egui::style::Margin::same(6.0))
+        .rounding(4.0)
+        .show(ui, |ui| {
+            if typing_name {
+                let mut matches: Vec<&ApplicationCommand> = commands
+                    .iter()
+                    .filter(|c| c.name.starts_with(name))
+                    .collect();
+                matches.sort_by(|a, b| a.name.cmp(&b.name));
+                if matches.is_empty() {
+                    ui.label(RichText::new("No matching commands").weak());
+                }
+                for command in matches.into_iter().take(MAX_SUGGESTIONS) {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(false, format!("/{}", command.name))
+                            .clicked()
+                        {
+                            replacement = Some(format!("/{} ", command.name));
+                        }
+                        ui.label(RichText::new(&command.description).small().weak());
+                    });
+                }
+                return;
+            }
+            let command = match commands.iter().find(|c| c.name == name) {
+                Some(command) => command,
+                None => {
+                    ui.label(RichText::new(format!("Unknown command /{}", name)).weak());
+                    return;
+                }
+            };
+            ui.label(RichText::new(format!("/{}", command.name)).strong());
+            ui.label(RichText::new(&command.description).small().weak());
+            let (options, arguments) = commands::current_options(command, rest);
+            // The option whose value is being typed, if the input ends in one.
+            let current = arguments
+                .last()
+                .filter(|_| !input.ends_with(' ') && !input.ends_with('"'))
+                .map(|(key, _)| *key);
+            for option in options {
+                let given = arguments.iter().any(|(key, _)| *key == option.name);
+                ui.horizontal_wrapped(|ui| {
+                    let label =
+                        RichText::new(format!("{}: {}", option.name, option.option_type.name()))
+                            .monospace();
+                    if given {
+                        ui.label(label.weak());
+                    } else if ui.selectable_label(false, label).clicked() {
+                        replacement = Some(if option.is_subcommand() {
+                            // Replaces the part of the name typed so far.
+                            let start = input
+                                .rfind(char::is_whitespace)
+                                .map(|i| i + 1)
+                                .unwrap_or(input.len());
+                            format!("{}{} ", &input[..start], option.name)
+                        } else {
+                            format!("{}{}:", with_space(input), option.name)
+                        });
+                    }
+                    if option.required {
+                        ui.label(RichText::new("required").small().strong());
+                    }
+                    ui.label(RichText::new(&option.description).small().weak());
+                });
+                if current == Some(option.name.as_str()) {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.add_space(12.0);
+                        for choice in &option.choices {
+                            if ui.small_button(&choice.name).clicked() {
+                                let start = input.rfind(':').map(|i| i + 1).unwrap_or(input.len());
+                                replacement =
+                                    Some(format!("{}\"{}\" ", &input[..start], choice.name));
+                            }
+                        }
+                    });
+                }
+            }
+        });
+    replacement
+}
+
+fn with_space(input: &str) -> String {
+    if input.ends_with(' ') {
+        input.to_string()
+    } else {
+        format!("{} ", input)
+    }
+}
@@ -0,0 +1,230 @@
+use egui::{Color32, RichText};
+
+use super::{images, Action, RenderContext};
+use crate::discord::cdn;
+use crate::discord::interactions::ComponentClick;
+use crate::discord::types::{ButtonStyle, ComponentType};
+use crate::{Message, MessageComponent, MessageEmoji};
+
+const EMOJI_SIZE: f32 = 18.0;
+
+/// Draws a message's action rows. Clicks become [`Action::Interact`], except
+/// link buttons, which open their URL directly.
+pub fn components(ui: &mut egui::Ui, message: &Message, cx: &mut RenderContext) {
+    for (row_index, row) in message.components.iter().enumerate() {
+        let id = egui::Id::new(("components", message.id, row_index));
+        match (&row.component_type, &row.components) {
+            (ComponentType::ActionRow, Some(children)) => {
+                ui.horizontal_wrapped(|ui| {
+                    for (index, component) in children.iter().enumerate() {
+                        self::component(ui, id.with(index), message, component, cx);
+                    }
+                });
+            }
+            _ => component(ui, id, message, row, cx),
+        }
+    }
+}
+
+fn component(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    message: &Message,
+    component: &MessageComponent,
+    cx: &mut RenderContext,
+) {
+    match component.component_type {
+        ComponentType::Button => button(ui, message, component, cx),
+        ComponentType::StringSelect => select_menu(ui, id, message, component, cx),
+        ComponentType::UserSelect
+        | ComponentType::RoleSelect
+        | ComponentType::MentionableSelect
+        | ComponentType::ChannelSelect => {
+            // These list guild members, roles or channels, which we cannot
+            // offer yet.
+            let placeholder = component.placeholder.as_deref().unwrap_or("Select");
+            ui.add_enabled(false, egui::Button::new(placeholder));
+        }
+        _ => {}
+    }
+}
+
+fn button(
+    ui: &mut egui::Ui,
+    message: &Message,
+    component: &MessageComponent,
+    cx: &mut RenderContext,
+) {
+    let style = component.style.unwrap_or(ButtonStyle::Secondary);
+    let fill = match style {
+        ButtonStyle::Primary => Color32::from_rgb(88, 101, 242),
+        ButtonStyle::Success => Color32::from_rgb(59, 165, 92),
+        ButtonStyle::Danger => Color32::from_rgb(237, 66, 69),
+        _ => Color32::from_rgb(79, 84, 92),
+    };
+    let mut text = component.label.clone().unwrap_or_default();
+    if style == ButtonStyle::Link {
+        text.push_str(" ↗");
+    }
+    let text = RichText::new(text).color(Color32::WHITE);
+    let button = match emoji(ui, component.emoji.as_ref()) {
+        Emoji::Image(texture) => {
+            egui::Button::image_and_text(texture, egui::vec2(EMOJI_SIZE, EMOJI_SIZE), text)
+        }
+        Emoji::Text(emoji) => egui::Button::new(
+            RichText::new(format!("{} {}", emoji, text.text())).color(Color32::WHITE),
+        ),
+        Emoji::None => egui::Button::new(text),
+    };
+    let enabled = !component.disabled.unwrap_or(false);
+    let response = ui.add_enabled(enabled, button.fill(fill));
+    if !response.clicked() {
+        return;
+    }
+    if style == ButtonStyle::Link {
+        if let Some(url) = &component.url {
+            ui.output().open_url(url);
+        }
+    } else if let Some(custom_id) = &component.custom_id {
+        cx.actions.push(Action::Interact(click(
+            message,
+            component,
+            custom_id,
+            Vec::new(),
+        )));
+    }
+}
+
+/// Single-choice menus send as soon as an option is picked; multi-choice ones
+/// collect ticks (kept in egui's temp data under `id`) until "Submit".
+fn select_menu(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    message: &Message,
+    component: &MessageComponent,
+    cx: &mut RenderContext,
+) {
+    let custom_id = match &component.custom_id {
+        Some(custom_id) => custom_id,
+        None => return,
+    };
+    let min = component.min_values.unwrap_or(1) as usize;
+    let max = component.max_values.unwrap_or(1) as usize;
+    let mut chosen = ui.data().get_temp::<Vec<String>>(id).unwrap_or_else(|| {
+        component
+            .options
+            .iter()
+            .filter(|o| o.default)
+            .map(|o| o.value.clone())
+            .collect()
+    });
+    let selected_text = if chosen.is_empty() {
+        component
+            .placeholder
+            .clone()
+            .unwrap_or_else(|| "Make a selection".to_string())
+    } else {
+        component
+            .options
+            .iter()
+            .filter(|o| chosen.contains(&o.value))
+            .map(|o| o.label.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let enabled = !component.disabled.unwrap_or(false);
+    let mut send = None;
+    ui.add_enabled_ui(enabled, |ui| {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(selected_text)
+            .width(240.0)
+            .show_ui(ui, |ui| {
+                for option in &component.options {
+                    let mut text = option.label.clone();
+                    if let Emoji::Text(glyph) = emoji(ui, option.emoji.as_ref()) {
+                        text = format!("{} {}", glyph, text);
+                    }
+                    let picked = chosen.contains(&option.value);
+                    let response = if max > 1 {
+                        let mut checked = picked;
+                        let response = ui.checkbox(&mut checked, text);
+                        if checked != picked {
+                            if checked && chosen.len() < max {
+                                chosen.push(option.value.clone());
+                            } else {
+                                chosen.retain(|v| v != &option.value);
+                            }
+                        }
+                        response
+                    } else {
+                        let response = ui.selectable_label(picked, text);
+                        if response.clicked() {
+                            chosen = vec![option.value.clone()];
+                            send = Some(chosen.clone());
+                        }
+                        response
+                    };
+                    if let Some(description) = &option.description {
+                        response.on_hover_text(description);
+                    }
+                }
+            });
+        if max > 1 {
+            let valid = (min..=max).contains(&chosen.len());
+            if ui.add_enabled(valid, egui::Button::new("Submit")).clicked() {
+                send = Some(chosen.clone());
+            }
+        }
+    });
+    ui.data().insert_temp(id, chosen);
+    if let Some(values) = send {
+        cx.actions.push(Action::Interact(click(
+            message, component, custom_id, values,
+        )));
+    }
+}
+
+fn click(
+    message: &Message,
+    component: &MessageComponent,
+    custom_id: &str,
+    values: Vec<String>,
+) -> ComponentClick {
+    ComponentClick {
+        // Interaction responses name their bot; plain bot messages are
+        // authored by it.
+        application_id: message.application_id.unwrap_or(message.author.id),
+        guild_id: message.guild_id,
+        channel_id: message.channel_id,
+        message_id: message.id,
+        message_flags: message.flags.map(|f| f.bits()).unwrap_or(0),
+        component_type: component.component_type,
+        custom_id: custom_id.to_string(),
+        values,
+    }
+}
+
+enum Emoji {
+    Image(egui::TextureId),
+    Text(String),
+    None,
+}
+
+/// Custom emoji are images (absent until loaded); standard ones are text.
+fn emoji(ui: &egui::Ui, emoji: Option<&MessageEmoji>) -> Emoji {
+    let emoji = match emoji {
+        Some(emoji) => emoji,
+        None => return Emoji::None,
+    };
+    match (emoji.id, &emoji.name) {
+        (Some(id), _) => {
+            let url = cdn::emoji(id, emoji.animated.unwrap_or(false), 32);
+            match images::texture(ui.ctx(), &url) {
+                Some(texture) => Emoji::Image(texture.id()),
+                None => Emoji::None,
+            }
+        }
+        (None, Some(name)) => Emoji::Text(name.clone()),
+        (None, None) => Emoji::None,
+    }
+}
@@ -0,0 +1,137 @@
+use egui::{Color32, RichText};
+
+use super::{images, members, messages};
+use crate::discord::cdn;
+use crate::discord::presence::Presences;
+use crate::discord::read_state::ReadStates;
+use crate::discord::snowflake::Snowflake;
+use crate::discord::types::ChannelType;
+use crate::PrivateChannel;
+
+const ICON_SIZE: f32 = 32.0;
+
+/// DMs and group DMs, most recently active first. Returns the one that was
+/// clicked.
+pub fn list(
+    ui: &mut egui::Ui,
+    channels: &[PrivateChannel],
+    selected: Option<Snowflake>,
+    read_states: &ReadStates,
+    presences: &Presences,
+) -> Option<Snowflake> {
+    let mut sorted: Vec<&PrivateChannel> = channels.iter().collect();
+    // Conversations without messages sort by when they were opened.
+    sorted.sort_by_key(|c| {
+        std::cmp::Reverse(
+            c.last_message_id
+                .max(read_states.last_message(c.id))
+                .unwrap_or(c.id),
+        )
+    });
+    if sorted.is_empty() {
+        ui.label(RichText::new("No conversations yet").weak());
+    }
+    let mut opened = None;
+    for channel in sorted {
+        ui.horizontal(|ui| {
+            icon(ui, channel, presences);
+            let mut name = RichText::new(channel.display_name());
+            if read_states.is_unread(channel.id) {
+                name = name.strong();
+            }
+            if ui
+                .selectable_label(selected == Some(channel.id), name)
+                .clicked()
+            {
+                opened = Some(channel.id);
+            }
+            if channel.channel_type == ChannelType::Dm {
+                let activity = channel
+                    .recipients
+                    .first()
+                    .and_then(|r| presences.get(r.id))
+                    .filter(|p| p.status.is_online())
+                    .and_then(|p| p.headline());
+                if let Some(activity) = activity {
+                    ui.label(RichText::new(activity.describe()).small().weak());
+                }
+            }
+            if channel.channel_type == ChannelType::GroupDm {
+                ui.label(
+                    RichText::new(format!("{} members", channel.recipients.len() + 1))
+                        .small()
+                        .weak(),
+                );
+            }
+        });
+    }
+    opened
+}
+
+/// The other person's avatar and status in a DM; a group's icon, or its
+/// initial, in a group DM.
+fn icon(ui: &mut egui::Ui, channel: &PrivateChannel, presences: &Presences) {
+    if channel.channel_type == ChannelType::Dm {
+        if let Some(recipient) = channel.recipients.first() {
+            let rect = messages::avatar(ui, recipient);
+            members::status_dot(ui, rect, presences.status(recipient.id));
+            return;
+        }
+    }
+    let size = egui::vec2(ICON_SIZE, ICON_SIZE);
+    if let Some(hash) = &channel.icon {
+        let url = cdn::channel_icon(channel.id, hash, 64);
+        if let Some(texture) = images::texture(ui.ctx(), &url) {
+            ui.image(texture.id(), size);
+            return;
+        }
+    }
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter();
+    painter.circle_filled(
+        rect.center(),
+        ICON_SIZE / 2.0,
+        Color32::from_rgb(88, 101, 242),
+    );
+    let initial = channel
+        .display_name()
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_default();
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        initial,
+        egui::FontId::proportional(ICON_SIZE / 2.0),
+        Color32::WHITE,
+    );
+}
+
+/// A field for starting a conversation with someone by user id (or a
+/// pasted `<@id>` mention). Returns the id once "Message" is clicked.
+pub fn new_message(ui: &mut egui::Ui, input: &mut String) -> Option<Snowflake> {
+    let mut user_id = None;
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(input)
+                .hint_text("User ID")
+                .desired_width(120.0),
+        );
+        let parsed = input
+            .trim()
+            .trim_start_matches("<@")
+            .trim_start_matches('!')
+            .trim_end_matches('>')
+            .parse::<u64>()
+            .ok()
+            .map(Snowflake);
+        if ui
+            .add_enabled(parsed.is_some(), egui::Button::new("Message"))
+            .clicked()
+        {
+            user_id = parsed;
+        }
+    });
+    user_id
+}
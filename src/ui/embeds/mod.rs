@@ -0,0 +1,183 @@
+use egui::{Color32, RichText};
+
+use super::{images, markdown, time, RenderContext};
+use crate::{MessageEmbed, MessageEmbedField, MessageMention};
+
+const MAX_WIDTH: f32 = 520.0;
+const THUMBNAIL_SIZE: f32 = 80.0;
+const ICON_SIZE: f32 = 20.0;
+
+/// Inline fields share a row, up to this many at a time.
+const FIELDS_PER_ROW: usize = 3;
+
+/// Draws one embed as a card with a coloured bar down its left edge. `id`
+/// must be unique per embed; it keys spoilers in the markdown parts.
+pub fn embed(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    embed: &MessageEmbed,
+    users: &[MessageMention],
+    cx: &mut RenderContext,
+) {
+    // Bare image and gif links come through as embeds with only a thumbnail,
+    // which Discord shows as the image itself.
+    if matches!(embed.embed_type.as_str(), "image" | "gifv")
+        && embed.title.is_none()
+        && embed.description.is_none()
+    {
+        if let Some(media) = &embed.thumbnail {
+            let size = images::fit_size(media.width, media.height, egui::vec2(400.0, 300.0));
+            let response = images::show(ui, media.source(), size);
+            if response.on_hover_text(&media.url).clicked() {
+                ui.output().open_url(&media.url);
+            }
+            return;
+        }
+    }
+
+    let bar_color = match embed.color {
+        Some(color) => Color32::from_rgb((color >> 16) as u8, (color >> 8) as u8, color as u8),
+        None => ui.visuals().widgets.noninteractive.bg_stroke.color,
+    };
+    let response = egui::Frame::none()
+        .fill(ui.visuals().faint_bg_color)
+        .inner_margin(egui::style::Margin {
+            left: 12.0,
+            right: 10.0,
+            top: 8.0,
+            bottom: 8.0,
+        })
+        .rounding(4.0)
+        .show(ui, |ui| {
+            ui.set_max_width(MAX_WIDTH);
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    let width = if embed.thumbnail.is_some() {
+                        MAX_WIDTH - THUMBNAIL_SIZE - 16.0
+                    } else {
+                        MAX_WIDTH
+                    };
+                    ui.set_max_width(width);
+                    body(ui, id, embed, users, cx);
+                });
+                if let Some(thumbnail) = &embed.thumbnail {
+                    let size = images::fit_size(
+                        thumbnail.width,
+                        thumbnail.height,
+                        egui::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+                    );
+                    images::show(ui, thumbnail.source(), size);
+                }
+            });
+            if let Some(image) = &embed.image {
+                let size = images::fit_size(image.width, image.height, egui::vec2(400.0, 300.0));
+                if images::show(ui, image.source(), size).clicked() {
+                    ui.output().open_url(&image.url);
+                }
+            }
+            if let Some(video) = &embed.video {
+                ui.hyperlink_to("▶ Play video", &video.url);
+            }
+            footer(ui, embed);
+        })
+        .response;
+    let rect = response.rect;
+    let bar = egui::Rect::from_min_size(rect.min, egui::vec2(4.0, rect.height()));
+    ui.painter().rect_filled(bar, 2.0, bar_color);
+}
+
+fn body(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    embed: &MessageEmbed,
+    users: &[MessageMention],
+    cx: &mut RenderContext,
+) {
+    if let Some(provider) = &embed.provider {
+        if let Some(name) = &provider.name {
+            let text = RichText::new(name).small().weak();
+            match &provider.url {
+                Some(url) => ui.hyperlink_to(text, url),
+                None => ui.label(text),
+            };
+        }
+    }
+    if let Some(author) = &embed.author {
+        ui.horizontal(|ui| {
+            if let Some(icon) = author.proxy_icon_url.as_ref().or(author.icon_url.as_ref()) {
+                images::show(ui, icon, egui::vec2(ICON_SIZE, ICON_SIZE));
+            }
+            let text = RichText::new(&author.name).small().strong();
+            match &author.url {
+                Some(url) => ui.hyperlink_to(text, url),
+                None => ui.label(text),
+            };
+        });
+    }
+    if let Some(title) = &embed.title {
+        let text = RichText::new(title).strong();
+        match &embed.url {
+            Some(url) => ui.hyperlink_to(text, url),
+            None => ui.label(text),
+        };
+    }
+    if let Some(description) = &embed.description {
+        markdown::show(ui, id.with("description"), description, users, cx);
+    }
+    fields(ui, id, &embed.fields, users, cx);
+}
+
+/// Lays fields out in rows: each non-inline field on its own, consecutive
+/// inline fields side by side.
+fn fields(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    fields: &[MessageEmbedField],
+    users: &[MessageMention],
+    cx: &mut RenderContext,
+) {
+    let mut index = 0;
+    while index < fields.len() {
+        let row_len = if fields[index].inline {
+            fields[index..]
+                .iter()
+                .take(FIELDS_PER_ROW)
+                .take_while(|f| f.inline)
+                .count()
+        } else {
+            1
+        };
+        let row = &fields[index..index + row_len];
+        ui.add_space(4.0);
+        ui.columns(row_len, |columns| {
+            for (offset, (column, field)) in columns.iter_mut().zip(row).enumerate() {
+                let id = id.with(("field", index + offset));
+                column.label(RichText::new(&field.name).small().strong());
+                markdown::show(column, id, &field.value, users, cx);
+            }
+        });
+        index += row_len;
+    }
+}
+
+fn footer(ui: &mut egui::Ui, embed: &MessageEmbed) {
+    let text = embed.footer.as_ref().map(|f| f.text.as_str());
+    let timestamp = embed.timestamp.as_ref().map(time::format_relative);
+    let line = match (text, timestamp) {
+        (Some(text), Some(timestamp)) => format!("{} • {}", text, timestamp),
+        (Some(text), None) => text.to_string(),
+        (None, Some(timestamp)) => timestamp,
+        (None, None) => return,
+    };
+    ui.horizontal(|ui| {
+        if let Some(footer) = &embed.footer {
+            if let Some(icon) = footer.proxy_icon_url.as_ref().or(footer.icon_url.as_ref()) {
+                images::show(ui, icon, egui::vec2(ICON_SIZE, ICON_SIZE));
+            }
+        }
+        let label = ui.label(RichText::new(line).small().weak());
+        if let Some(timestamp) = &embed.timestamp {
+            label.on_hover_text(time::format_full(timestamp));
+        }
+    });
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use egui::RichText;
+
+use super::time;
+use crate::discord::forum::{self, ForumTag, SortOrder};
+use crate::discord::snowflake::Snowflake;
+use crate::discord::threads::Thread;
+
+/// The bar above a forum's posts: sort order, tag filter and "New Post".
+/// Returns whether "New Post" was clicked.
+pub fn toolbar(
+    ui: &mut egui::Ui,
+    tags: &[ForumTag],
+    sort: &mut SortOrder,
+    tag: &mut Option<Snowflake>,
+    can_post: bool,
+) -> bool {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source("forum_sort")
+            .selected_text(sort.name())
+            .show_ui(ui, |ui| {
+                for order in [SortOrder::LatestActivity, SortOrder::CreationDate] {
+                    ui.selectable_value(sort, order, order.name());
+                }
+            });
+        if !tags.is_empty() {
+            let selected = tags
+                .iter()
+                .find(|t| Some(t.id) == *tag)
+                .map(|t| t.label())
+                .unwrap_or_else(|| "All tags".to_string());
+            egui::ComboBox::from_id_source("forum_tag")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(tag, None, "All tags");
+                    for forum_tag in tags {
+                        ui.selectable_value(tag, Some(forum_tag.id), forum_tag.label());
+                    }
+                });
+        }
+        ui.add_enabled(can_post, egui::Button::new("New Post"))
+            .clicked()
+    })
+    .inner
+}
+
+fn tag_chip(ui: &mut egui::Ui, label: String) {
+    egui::Frame::none()
+        .fill(ui.visuals().faint_bg_color)
+        .inner_margin(egui::style::Margin::symmetric(6.0, 2.0))
+        .rounding(8.0)
+        .show(ui, |ui| {
+            ui.label(RichText::new(label).small());
+        });
+}
+
+/// One post in the list: its title, tags, replies and last activity.
+/// Returns whether it was clicked.
+pub fn post(ui: &mut egui::Ui, post: &Thread, tags: &[ForumTag]) -> bool {
+    egui::Frame::group(ui.style())
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            let clicked = ui
+                .add(
+                    egui::Label::new(RichText::new(&post.name).strong())
+                        .sense(egui::Sense::click()),
+                )
+                .clicked();
+            let applied: Vec<&ForumTag> = tags
+                .iter()
+                .filter(|t| post.applied_tags.contains(&t.id))
+                .collect();
+            if !applied.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for tag in applied {
+                        tag_chip(ui, tag.label());
+                    }
+                });
+            }
+            let active = DateTime::<Utc>::from(forum::last_activity(post).created_at());
+            let mut details = format!(
+                "{} replies · active {}",
+                post.message_count,
+                time::format_relative(&active)
+            );
+            if post.is_archived() {
+                details.push_str(" · archived");
+            }
+            ui.label(RichText::new(details).small().weak());
+            clicked
+        })
+        .inner
+}
+
+/// The form for a new post. Moderated tags are offered only to those who
+/// may apply them. Returns whether "Post" was clicked.
+pub fn post_form(
+    ui: &mut egui::Ui,
+    tags: &[ForumTag],
+    title: &mut String,
+    body: &mut String,
+    selected: &mut Vec<Snowflake>,
+    can_moderate: bool,
+    require_tag: bool,
+) -> bool {
+    ui.add(egui::TextEdit::singleline(title).hint_text("Post title"));
+    let offered: Vec<&ForumTag> = tags
+        .iter()
+        .filter(|t| can_moderate || !t.moderated)
+        .collect();
+    if !offered.is_empty() {
+        ui.label(
+            RichText::new(if require_tag {
+                "TAGS (REQUIRED)"
+            } else {
+                "TAGS"
+            })
+            .small()
+            .strong(),
+        );
+        ui.horizontal_wrapped(|ui| {
+            for tag in offered {
+                let mut on = selected.contains(&tag.id);
+                if ui.toggle_value(&mut on, tag.label()).changed() {
+                    if on {
+                        selected.push(tag.id);
+                    } else {
+                        selected.retain(|id| *id != tag.id);
+                    }
+                }
+            }
+        });
+    }
+    ui.add(
+        egui::TextEdit::multiline(body)
+            .hint_text("Message")
+            .desired_rows(4),
+    );
+    let ready = !title.trim().is_empty()
+        && !body.trim().is_empty()
+        && (!require_tag || !selected.is_empty());
+    ui.add_enabled(ready, egui::Button::new("Post")).clicked()
+}
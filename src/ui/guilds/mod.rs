@@ -0,0 +1,55 @@
+use egui::Color32;
+
+use super::images;
+use crate::discord::cdn;
+use crate::Guild;
+
+pub const ICON_SIZE: f32 = 32.0;
+
+/// The guild's icon, or its initials on a grey circle when it has none (or
+/// while the icon loads), as Discord's sidebar does.
+pub fn icon(ui: &mut egui::Ui, guild: &Guild) -> egui::Response {
+    let size = egui::vec2(ICON_SIZE, ICON_SIZE);
+    if let Some(hash) = &guild.icon {
+        let url = cdn::guild_icon(guild.id, hash, 64);
+        if let Some(texture) = images::texture(ui.ctx(), &url) {
+            return ui.add(egui::Image::new(texture.id(), size).sense(egui::Sense::click()));
+        }
+    }
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    let painter = ui.painter();
+    painter.circle_filled(
+        rect.center(),
+        ICON_SIZE / 2.0,
+        Color32::from_rgb(54, 57, 63),
+    );
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        initials(&guild.name),
+        egui::FontId::proportional(ICON_SIZE / 3.0),
+        Color32::WHITE,
+    );
+    response
+}
+
+/// The small white pill beside guilds with unread messages. Takes up its
+/// space either way so the list stays aligned.
+pub fn unread_pip(ui: &mut egui::Ui, unread: bool) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(4.0, ICON_SIZE), egui::Sense::hover());
+    if unread {
+        ui.painter().rect_filled(
+            egui::Rect::from_center_size(rect.center(), egui::vec2(4.0, 8.0)),
+            2.0,
+            Color32::WHITE,
+        );
+    }
+}
+
+/// "Rust Programming Language" becomes "RPL".
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(3)
+        .collect()
+}
@@ -0,0 +1,102 @@
+use egui::text::LayoutJob;
+use egui::util::cache::{ComputerMut, FrameCache};
+use egui::{Color32, FontId, Stroke, TextFormat};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+lazy_static::lazy_static! {
+    static ref SYNTAXES: SyntaxSet = {
+        let mut builder = SyntaxSet::load_defaults_newlines().into_builder();
+        let toml = SyntaxDefinition::load_from_str(include_str!("toml.sublime-syntax"), true, None)
+            .expect("bundled TOML grammar is valid");
+        builder.add(toml);
+        builder.build()
+    };
+    static ref THEMES: ThemeSet = ThemeSet::load_defaults();
+}
+
+#[derive(Default)]
+struct Highlighter;
+
+/// Keyed by language, code, monospace font size (as bits, since `f32` is not
+/// hashable) and whether the dark theme is in use.
+impl ComputerMut<(&str, &str, u32, bool), LayoutJob> for Highlighter {
+    fn compute(&mut self, (language, code, size, dark_mode): (&str, &str, u32, bool)) -> LayoutJob {
+        let font_id = FontId::monospace(f32::from_bits(size));
+        highlight(language, code, font_id.clone(), dark_mode)
+            .unwrap_or_else(|| plain(code, font_id, dark_mode))
+    }
+}
+
+type HighlightCache = FrameCache<LayoutJob, Highlighter>;
+
+/// Lays out `code` coloured for `language` (a fence tag such as `rust`, `sh`
+/// or `toml`), falling back to plain monospace for unknown languages. Cached
+/// across frames.
+pub fn code_job(ui: &egui::Ui, language: Option<&str>, code: &str) -> LayoutJob {
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let dark_mode = ui.visuals().dark_mode;
+    match language {
+        Some(language) => ui.memory().caches.cache::<HighlightCache>().get((
+            language,
+            code,
+            font_id.size.to_bits(),
+            dark_mode,
+        )),
+        None => plain(code, font_id, dark_mode),
+    }
+}
+
+fn theme(dark_mode: bool) -> &'static Theme {
+    let name = if dark_mode {
+        "base16-ocean.dark"
+    } else {
+        "InspiredGitHub"
+    };
+    &THEMES.themes[name]
+}
+
+fn highlight(language: &str, code: &str, font_id: FontId, dark_mode: bool) -> Option<LayoutJob> {
+    // Fence tags people use that are not a grammar's name or extension.
+    let token = match language.to_ascii_lowercase().as_str() {
+        "shell" | "console" | "terminal" => "bash".to_string(),
+        other => other.to_string(),
+    };
+    let syntax = SYNTAXES.find_syntax_by_token(&token)?;
+    let mut lines = HighlightLines::new(syntax, theme(dark_mode));
+    let mut job = LayoutJob::default();
+    for line in LinesWithEndings::from(code) {
+        for (style, text) in lines.highlight_line(line, &SYNTAXES).ok()? {
+            let fg = style.foreground;
+            let color = Color32::from_rgba_unmultiplied(fg.r, fg.g, fg.b, fg.a);
+            let underline = if style.font_style.contains(FontStyle::UNDERLINE) {
+                Stroke::new(1.0, color)
+            } else {
+                Stroke::none()
+            };
+            job.append(
+                text,
+                0.0,
+                TextFormat {
+                    font_id: font_id.clone(),
+                    color,
+                    italics: style.font_style.contains(FontStyle::ITALIC),
+                    underline,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    Some(job)
+}
+
+fn plain(code: &str, font_id: FontId, dark_mode: bool) -> LayoutJob {
+    let color = if dark_mode {
+        Color32::from_gray(220)
+    } else {
+        Color32::from_gray(40)
+    };
+    LayoutJob::simple(code.to_string(), font_id, color, f32::INFINITY)
+}
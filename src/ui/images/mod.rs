@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use egui::{ColorImage, TextureFilter, TextureHandle};
+use futures_util::StreamExt;
+use poll_promise::Promise;
+
+/// Decoded textures are dropped, least recently used first, once together
+/// they take more than this much memory. They are reloaded from disk when
+/// next drawn.
+const MEMORY_BUDGET: usize = 256 * 1024 * 1024;
+
+/// Once the disk cache grows past this, the files written longest ago are
+/// deleted until it fits again.
+const DISK_BUDGET: u64 = 512 * 1024 * 1024;
+
+/// How long a failed image waits before it is requested again.
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Bytes in the disk cache, counted when it is first written to this run and
+/// kept up to date from then on.
+static DISK_USED: AtomicU64 = AtomicU64::new(0);
+static DISK_COUNTED: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    static ref IMAGE_CACHE: Mutex<ImageCache> = Mutex::new(ImageCache::default());
+}
+
+#[derive(Default)]
+struct ImageCache {
+    entries: HashMap<String, Entry>,
+    /// Bumped on every lookup; entries remember when they were last used.
+    clock: u64,
+    /// Bytes held by loaded textures.
+    used: usize,
+}
+
+struct Entry {
+    state: State,
+    last_used: u64,
+}
+
+enum State {
+    Loading(Promise<Result<ColorImage, String>>),
+    Loaded(TextureHandle),
+    /// When loading failed; it is tried again after `RETRY_DELAY`.
+    Failed(Instant),
+}
+
+impl ImageCache {
+    /// Frees the least recently used textures until we are within budget.
+    fn evict(&mut self) {
+        while self.used > MEMORY_BUDGET {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| matches!(entry.state, State::Loaded(_)))
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+            let url = match oldest {
+                Some(url) => url,
+                None => break,
+            };
+            if let Some(Entry {
+                state: State::Loaded(texture),
+                ..
+            }) = self.entries.remove(&url)
+            {
+                self.used -= texture_bytes(&texture);
+            }
+        }
+    }
+}
+
+fn texture_bytes(texture: &TextureHandle) -> usize {
+    let [width, height] = texture.size();
+    width * height * 4
+}
+
+/// Where downloaded images are kept between runs, under the platform's cache
+/// directory. `None` if it cannot be determined.
+fn disk_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("discord-client").join("images"))
+}
+
+/// 64-bit FNV-1a. Unlike the standard library's hashers its output never
+/// changes, so files cached by an older build are still found.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn disk_path(dir: &Path, url: &str) -> PathBuf {
+    dir.join(format!("{:016x}", fnv1a(url.as_bytes())))
+}
+
+/// Deletes the files written longest ago until the cache fits its budget,
+/// and records what is left.
+async fn prune(dir: &Path) {
+    let mut files = Vec::new();
+    if let Ok(mut entries) = async_std::fs::read_dir(dir).await {
+        while let Some(Ok(entry)) = entries.next().await {
+            if let Ok(metadata) = entry.metadata().await {
+                let written = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((written, metadata.len(), entry.path()));
+            }
+        }
+    }
+    files.sort_by_key(|(written, _, _)| *written);
+    let mut used: u64 = files.iter().map(|(_, len, _)| len).sum();
+    for (_, len, path) in files {
+        if used <= DISK_BUDGET {
+            break;
+        }
+        if async_std::fs::remove_file(&path).await.is_ok() {
+            used -= len;
+        }
+    }
+    DISK_USED.store(used, Ordering::Relaxed);
+}
+
+/// Writes a downloaded image to the disk cache, pruning it when this takes
+/// it over budget.
+async fn store(dir: &Path, path: &Path, bytes: &[u8]) {
+    // Failing to cache is not worth failing the image over.
+    if async_std::fs::create_dir_all(dir).await.is_err()
+        || async_std::fs::write(path, bytes).await.is_err()
+    {
+        return;
+    }
+    let first = !DISK_COUNTED.swap(true, Ordering::Relaxed);
+    let len = bytes.len() as u64;
+    if first || DISK_USED.fetch_add(len, Ordering::Relaxed) + len > DISK_BUDGET {
+        prune(dir).await;
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<ColorImage, String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|err| format!("Failed to decode image: {}", err))?
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    let response = reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    let bytes = response.bytes().await.map_err(|err| err.to_string())?;
+    Ok(bytes.to_vec())
+}
+
+/// Reads `url` from the disk cache, or downloads it and caches it once it
+/// has decoded. A cached file that no longer decodes is replaced.
+async fn download(url: String) -> Result<ColorImage, String> {
+    let dir = disk_dir();
+    let path = dir.as_deref().map(|dir| disk_path(dir, &url));
+    if let Some(path) = &path {
+        if let Ok(bytes) = async_std::fs::read(path).await {
+            match decode(&bytes) {
+                Ok(image) => return Ok(image),
+                Err(_) => {
+                    let _ = async_std::fs::remove_file(path).await;
+                }
+            }
+        }
+    }
+    let bytes = fetch(&url).await?;
+    let image = decode(&bytes)?;
+    if let (Some(dir), Some(path)) = (&dir, &path) {
+        store(dir, path, &bytes).await;
+    }
+    Ok(image)
+}
+
+fn load(ctx: &egui::Context, url: &str) -> State {
+    let url = url.to_string();
+    let ctx = ctx.clone();
+    State::Loading(Promise::spawn_async(async move {
+        let result = download(url).await;
+        ctx.request_repaint();
+        result
+    }))
+}
+
+/// The texture for `url` once it has been downloaded (or read from the disk
+/// cache) and decoded. The first call starts loading; the UI is repainted
+/// when it finishes.
+pub fn texture(ctx: &egui::Context, url: &str) -> Option<TextureHandle> {
+    let mut cache = IMAGE_CACHE.lock().unwrap();
+    cache.clock += 1;
+    let clock = cache.clock;
+    let entry = cache
+        .entries
+        .entry(url.to_string())
+        .or_insert_with(|| Entry {
+            state: load(ctx, url),
+            last_used: clock,
+        });
+    entry.last_used = clock;
+    if let State::Failed(at) = entry.state {
+        if at.elapsed() >= RETRY_DELAY {
+            entry.state = load(ctx, url);
+        }
+    }
+    let mut loaded = 0;
+    if let State::Loading(promise) = &entry.state {
+        match promise.ready() {
+            Some(Ok(image)) => {
+                let texture = ctx.load_texture(url, image.clone(), TextureFilter::Linear);
+                loaded = texture_bytes(&texture);
+                entry.state = State::Loaded(texture);
+            }
+            Some(Err(err)) => {
+                println!("Failed to load {}: {}", url, err);
+                entry.state = State::Failed(Instant::now());
+            }
+            None => {}
+        }
+    }
+    let texture = match &entry.state {
+        State::Loaded(texture) => Some(texture.clone()),
+        _ => None,
+    };
+    if loaded > 0 {
+        cache.used += loaded;
+        cache.evict();
+    }
+    texture
+}
+
+/// Draws the image at `url` at `size`, or a placeholder of the same size
+/// while it loads so the layout does not jump.
+pub fn show(ui: &mut egui::Ui, url: &str, size: egui::Vec2) -> egui::Response {
+    match texture(ui.ctx(), url) {
+        Some(texture) => ui.add(egui::Image::new(texture.id(), size).sense(egui::Sense::click())),
+        None => {
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            ui.painter()
+                .rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+            response
+        }
+    }
+}
+
+/// Scales declared `width` × `height` down to fit within `max`, keeping the
+/// aspect ratio. Unknown dimensions get `max` itself.
+pub fn fit_size(width: Option<u64>, height: Option<u64>, max: egui::Vec2) -> egui::Vec2 {
+    match (width, height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => {
+            let size = egui::vec2(width as f32, height as f32);
+            let scale = (max.x / size.x).min(max.y / size.y).min(1.0);
+            size * scale
+        }
+        _ => max,
+    }
+}
@@ -0,0 +1,386 @@
+use std::sync::Arc;
+
+use egui::text::LayoutJob;
+use egui::util::cache::{ComputerMut, FrameCache};
+use egui::{Color32, FontId, Sense, Stroke, TextFormat, TextStyle};
+
+use super::{highlight, images, time, Action, RenderContext};
+use crate::discord::cdn;
+use crate::discord::markdown::{self, Block, Inline};
+use crate::discord::snowflake::Snowflake;
+use crate::MessageMention;
+
+#[derive(Default)]
+struct Parser;
+
+impl ComputerMut<&str, Arc<Vec<Block>>> for Parser {
+    fn compute(&mut self, source: &str) -> Arc<Vec<Block>> {
+        Arc::new(markdown::parse(source))
+    }
+}
+
+type ParseCache = FrameCache<Arc<Vec<Block>>, Parser>;
+
+/// What inline tokens are resolved against while drawing one message.
+struct Content<'a, 'b> {
+    /// Users mentioned by the message, which Discord sends along with it.
+    users: &'a [MessageMention],
+    cx: &'a mut RenderContext<'b>,
+}
+
+/// Parses (cached across frames) and draws `source`. `id` must be unique per
+/// message; it keys which spoilers have been revealed.
+pub fn show(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    source: &str,
+    users: &[MessageMention],
+    cx: &mut RenderContext,
+) {
+    let blocks = ui.memory().caches.cache::<ParseCache>().get(source);
+    show_blocks(ui, id, &blocks, &mut Content { users, cx });
+}
+
+fn show_blocks(ui: &mut egui::Ui, id: egui::Id, blocks: &[Block], content: &mut Content) {
+    for (index, block) in blocks.iter().enumerate() {
+        let id = id.with(index);
+        match block {
+            Block::Paragraph(inlines) => paragraph(ui, id, inlines, Style::default(), content),
+            Block::Heading {
+                level,
+                content: inner,
+            } => {
+                let style = Style {
+                    heading: Some(*level),
+                    bold: true,
+                    ..Default::default()
+                };
+                paragraph(ui, id, inner, style, content);
+            }
+            Block::Quote(inner) => quote(ui, id, inner, content),
+            Block::CodeBlock { language, code } => code_block(ui, id, language.as_deref(), code),
+            Block::List { start, items } => {
+                for (number, item) in items.iter().enumerate() {
+                    let marker = match start {
+                        Some(start) => format!("{}.", start + number as u64),
+                        None => "•".to_string(),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(marker);
+                        ui.vertical(|ui| {
+                            let id = id.with(number);
+                            paragraph(ui, id, &item.content, Style::default(), content);
+                            show_blocks(ui, id, &item.children, content);
+                        });
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn quote(ui: &mut egui::Ui, id: egui::Id, blocks: &[Block], content: &mut Content) {
+    let response = ui.horizontal(|ui| {
+        ui.add_space(10.0);
+        ui.vertical(|ui| show_blocks(ui, id, blocks, content));
+    });
+    let rect = response.response.rect;
+    let bar = egui::Rect::from_min_size(rect.min, egui::vec2(3.0, rect.height()));
+    ui.painter().rect_filled(
+        bar,
+        1.0,
+        ui.visuals().widgets.noninteractive.bg_stroke.color,
+    );
+}
+
+/// A highlighted code block with a copy button. Long lines scroll
+/// horizontally instead of wrapping.
+fn code_block(ui: &mut egui::Ui, id: egui::Id, language: Option<&str>, code: &str) {
+    egui::Frame::none()
+        .fill(ui.visuals().code_bg_color)
+        .inner_margin(egui::style::Margin::same(6.0))
+        .rounding(4.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if let Some(language) = language {
+                    ui.label(egui::RichText::new(language).small().weak());
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Copy").clicked() {
+                        ui.output().copied_text = code.to_string();
+                    }
+                });
+            });
+            let job = highlight::code_job(ui, language, code);
+            egui::ScrollArea::horizontal().id_source(id).show(ui, |ui| {
+                ui.add(egui::Label::new(job).wrap(false));
+            });
+        });
+}
+
+#[derive(Clone, Copy, Default)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    spoiler: bool,
+    code: bool,
+    heading: Option<u8>,
+}
+
+fn paragraph(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    inlines: &[Inline],
+    style: Style,
+    content: &mut Content,
+) {
+    let revealed = ui.data().get_temp::<bool>(id).unwrap_or(false);
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        let mut renderer = InlineRenderer {
+            ui,
+            content,
+            job: LayoutJob::default(),
+            revealed,
+            clicked: false,
+        };
+        renderer.inlines(inlines, style);
+        renderer.flush();
+        if renderer.clicked {
+            renderer.ui.data().insert_temp(id, !revealed);
+        }
+    });
+}
+
+/// Accumulates styled text into a [`LayoutJob`], flushing it as a label
+/// whenever a separate widget (such as a link) has to be placed inline.
+struct InlineRenderer<'a, 'b, 'c> {
+    ui: &'a mut egui::Ui,
+    content: &'a mut Content<'b, 'c>,
+    job: LayoutJob,
+    revealed: bool,
+    clicked: bool,
+}
+
+impl InlineRenderer<'_, '_, '_> {
+    fn inlines(&mut self, inlines: &[Inline], style: Style) {
+        for inline in inlines {
+            match inline {
+                Inline::Text(text) => self.text(text, style),
+                Inline::Bold(inner) => self.inlines(
+                    inner,
+                    Style {
+                        bold: true,
+                        ..style
+                    },
+                ),
+                Inline::Italic(inner) => self.inlines(
+                    inner,
+                    Style {
+                        italic: true,
+                        ..style
+                    },
+                ),
+                Inline::Underline(inner) => self.inlines(
+                    inner,
+                    Style {
+                        underline: true,
+                        ..style
+                    },
+                ),
+                Inline::Strikethrough(inner) => self.inlines(
+                    inner,
+                    Style {
+                        strikethrough: true,
+                        ..style
+                    },
+                ),
+                Inline::Spoiler(inner) => self.inlines(
+                    inner,
+                    Style {
+                        spoiler: true,
+                        ..style
+                    },
+                ),
+                Inline::Code(code) => self.text(
+                    code,
+                    Style {
+                        code: true,
+                        ..style
+                    },
+                ),
+                Inline::Link { text, url } => self.link(&markdown::plain_text(text), url, style),
+                Inline::Url(url) => self.link(url, url, style),
+                Inline::UserMention(id) => {
+                    let name = match self.content.users.iter().find(|u| u.id == *id) {
+                        Some(user) => format!("@{}", user.username),
+                        None => "@unknown-user".to_string(),
+                    };
+                    self.pill(&name, None, Action::OpenUser(*id), style);
+                }
+                Inline::ChannelMention(id) => {
+                    let channels = self.content.cx.channels;
+                    let name = match channels.iter().find(|c| c.id == *id) {
+                        Some(channel) => format!("#{}", channel.name),
+                        None => "#unknown-channel".to_string(),
+                    };
+                    self.pill(&name, None, Action::OpenChannel(*id), style);
+                }
+                Inline::RoleMention(id) => {
+                    let roles = self.content.cx.roles;
+                    match roles.iter().find(|r| r.id == *id) {
+                        Some(role) => {
+                            let color = (role.color != 0).then(|| role_color(role.color));
+                            self.pill(&format!("@{}", role.name), color, Action::None, style);
+                        }
+                        // Another guild's role, whose name we do not have.
+                        None if roles.is_empty() => self.text("@unknown-role", style),
+                        None => self.pill("@deleted-role", None, Action::None, style),
+                    }
+                }
+                Inline::MassMention(name) => {
+                    self.pill(&format!("@{}", name), None, Action::None, style)
+                }
+                Inline::Emoji { name, id, animated } => self.emoji(name, *id, *animated, style),
+                Inline::Timestamp { unix, style: tag } => match time::format_tag(*unix, *tag) {
+                    Some((text, full)) => {
+                        self.flush();
+                        let mut format = self.format(style);
+                        format.background = self.ui.visuals().code_bg_color;
+                        self.ui
+                            .label(LayoutJob::single_section(text, format))
+                            .on_hover_text(full);
+                    }
+                    None => self.text(&unix.to_string(), style),
+                },
+                Inline::LineBreak => self.text("\n", style),
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str, style: Style) {
+        let format = self.format(style);
+        self.job.append(text, 0.0, format);
+    }
+
+    fn link(&mut self, text: &str, url: &str, style: Style) {
+        if style.spoiler && !self.revealed {
+            self.text(text, style);
+            return;
+        }
+        self.flush();
+        let mut format = self.format(style);
+        format.color = self.ui.visuals().hyperlink_color;
+        let job = LayoutJob::single_section(text.to_string(), format);
+        self.ui.hyperlink_to(job, url).on_hover_text(url);
+    }
+
+    /// A highlighted, clickable name standing in for an id.
+    fn pill(&mut self, text: &str, color: Option<Color32>, action: Action, style: Style) {
+        if style.spoiler && !self.revealed {
+            self.text(text, style);
+            return;
+        }
+        self.flush();
+        let color = color.unwrap_or(self.ui.visuals().hyperlink_color);
+        let mut format = self.format(style);
+        format.color = color;
+        format.background = color.linear_multiply(0.15);
+        let job = LayoutJob::single_section(text.to_string(), format);
+        let sense = match action {
+            Action::None => Sense::hover(),
+            _ => Sense::click(),
+        };
+        let response = self.ui.add(egui::Label::new(job).sense(sense));
+        if response.clicked() {
+            self.content.cx.actions.push(action);
+        }
+    }
+
+    fn emoji(&mut self, name: &str, id: Snowflake, animated: bool, style: Style) {
+        if style.spoiler && !self.revealed {
+            self.text(&format!(":{}:", name), style);
+            return;
+        }
+        self.flush();
+        let size = self.format(style).font_id.size * 1.4;
+        let url = cdn::emoji(id, animated, 48);
+        images::show(self.ui, &url, egui::vec2(size, size)).on_hover_text(format!(":{}:", name));
+    }
+
+    fn flush(&mut self) {
+        if self.job.text.is_empty() {
+            return;
+        }
+        let job = std::mem::take(&mut self.job);
+        let has_spoiler = job
+            .sections
+            .iter()
+            .any(|s| s.format.background == spoiler_color(self.ui));
+        let label = egui::Label::new(job);
+        if has_spoiler {
+            let response = self.ui.add(label.sense(Sense::click()));
+            if response.on_hover_text("Click to reveal").clicked() {
+                self.clicked = true;
+            }
+        } else {
+            self.ui.add(label);
+        }
+    }
+
+    fn format(&self, style: Style) -> TextFormat {
+        let visuals = self.ui.visuals();
+        let body = self.ui.style().text_styles[&TextStyle::Body].size;
+        let size = match style.heading {
+            Some(1) => body * 1.6,
+            Some(2) => body * 1.35,
+            Some(_) => body * 1.15,
+            None => body,
+        };
+        let mut color = if style.bold {
+            visuals.strong_text_color()
+        } else {
+            visuals.text_color()
+        };
+        let mut format = TextFormat {
+            font_id: if style.code {
+                FontId::monospace(size)
+            } else {
+                FontId::proportional(size)
+            },
+            italics: style.italic,
+            ..Default::default()
+        };
+        if style.code {
+            format.background = visuals.code_bg_color;
+        }
+        if style.spoiler {
+            format.background = spoiler_color(self.ui);
+            if !self.revealed {
+                color = format.background;
+            }
+        }
+        if style.underline {
+            format.underline = Stroke::new(1.0, color);
+        }
+        if style.strikethrough {
+            format.strikethrough = Stroke::new(1.0, color);
+        }
+        format.color = color;
+        format
+    }
+}
+
+fn role_color(color: u64) -> Color32 {
+    Color32::from_rgb((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
+
+fn spoiler_color(ui: &egui::Ui) -> Color32 {
+    if ui.visuals().dark_mode {
+        Color32::from_gray(32)
+    } else {
+        Color32::from_gray(200)
+    }
+}
@@ -0,0 +1,177 @@
+use std::ops::Range;
+
+use egui::{Color32, RichText};
+
+use super::images;
+use crate::discord::cdn;
+use crate::discord::members::{ListItem, Member, MemberList};
+use crate::discord::permissions::Role;
+use crate::discord::presence::{Presences, UserPresence};
+use crate::discord::snowflake::Snowflake;
+use crate::discord::types::Status;
+
+/// Every row is this tall so the list can be virtualized.
+const ROW_HEIGHT: f32 = 36.0;
+const AVATAR_SIZE: f32 = 28.0;
+
+pub fn status_color(status: Status) -> Color32 {
+    match status {
+        Status::Online => Color32::from_rgb(59, 165, 92),
+        Status::Idle => Color32::from_rgb(250, 166, 26),
+        Status::Dnd => Color32::from_rgb(237, 66, 69),
+        Status::Invisible | Status::Offline => Color32::from_rgb(116, 127, 141),
+    }
+}
+
+/// Marks `status` on the bottom right corner of the avatar at `avatar`.
+pub fn status_dot(ui: &egui::Ui, avatar: egui::Rect, status: Status) {
+    let painter = ui.painter();
+    let dot = avatar.right_bottom() - egui::vec2(4.0, 4.0);
+    painter.circle_filled(dot, 5.0, ui.visuals().window_fill());
+    painter.circle_filled(dot, 3.5, status_color(status));
+}
+
+/// The member sidebar. Only the rows in view are laid out, so guilds with
+/// many thousands of members cost no more than small ones. Returns the
+/// member that was clicked and the rows that were visible.
+pub fn panel(
+    ui: &mut egui::Ui,
+    list: &MemberList,
+    roles: &[Role],
+    presences: &Presences,
+) -> (Option<Snowflake>, Range<usize>) {
+    ui.label(
+        RichText::new(format!(
+            "{} members, {} online",
+            list.member_count, list.online_count
+        ))
+        .small()
+        .weak(),
+    );
+    let mut clicked = None;
+    let visible = egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show_rows(ui, ROW_HEIGHT, list.rows.len(), |ui, range| {
+            for row in &list.rows[range.clone()] {
+                let size = egui::vec2(ui.available_width(), ROW_HEIGHT);
+                ui.allocate_ui_with_layout(
+                    size,
+                    egui::Layout::left_to_right(egui::Align::Center),
+                    |ui| {
+                        ui.set_min_size(size);
+                        match row {
+                            Some(ListItem::Group(group)) => {
+                                let name = match group.id.as_str() {
+                                    "online" => "Online".to_string(),
+                                    "offline" => "Offline".to_string(),
+                                    id => roles
+                                        .iter()
+                                        .find(|r| r.id.to_string() == id)
+                                        .map(|r| r.name.clone())
+                                        .unwrap_or_else(|| "Unknown role".to_string()),
+                                };
+                                ui.label(
+                                    RichText::new(format!(
+                                        "{} — {}",
+                                        name.to_uppercase(),
+                                        group.count
+                                    ))
+                                    .small()
+                                    .strong(),
+                                );
+                            }
+                            Some(ListItem::Member(member)) => {
+                                // Presence updates can be newer than the list's copy.
+                                let presence =
+                                    presences.get(member.user.id).or(member.presence.as_ref());
+                                if member_row(ui, member, roles, presence).clicked() {
+                                    clicked = Some(member.user.id);
+                                }
+                            }
+                            None => {
+                                let (rect, _) = ui.allocate_exact_size(
+                                    egui::vec2(ui.available_width() * 0.6, 12.0),
+                                    egui::Sense::hover(),
+                                );
+                                ui.painter()
+                                    .rect_filled(rect, 6.0, ui.visuals().faint_bg_color);
+                            }
+                        }
+                    },
+                );
+            }
+            range
+        })
+        .inner;
+    (clicked, visible)
+}
+
+fn member_row(
+    ui: &mut egui::Ui,
+    member: &Member,
+    roles: &[Role],
+    presence: Option<&UserPresence>,
+) -> egui::Response {
+    let status = presence.map(|p| p.status).unwrap_or_default();
+    avatar(ui, member, status);
+    let mut name = RichText::new(member.display_name());
+    if let Some(color) = role_color(member, roles) {
+        name = name.color(color);
+    }
+    if !status.is_online() {
+        name = name.weak();
+    }
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing.y = 0.0;
+        let response = ui
+            .horizontal(|ui| {
+                let response = ui.add(egui::Label::new(name).sense(egui::Sense::click()));
+                if member.user.bot {
+                    ui.label(RichText::new("BOT").small().strong());
+                }
+                response
+            })
+            .inner;
+        if let Some(activity) = presence
+            .filter(|p| p.status.is_online())
+            .and_then(|p| p.headline())
+        {
+            ui.add(egui::Label::new(RichText::new(activity.describe()).small().weak()).wrap(false));
+        }
+        response
+    })
+    .inner
+}
+
+/// The colour of the member's highest role that has one.
+fn role_color(member: &Member, roles: &[Role]) -> Option<Color32> {
+    let color = roles
+        .iter()
+        .filter(|r| r.color != 0 && member.roles.contains(&r.id))
+        .max_by_key(|r| r.position)?
+        .color;
+    Some(Color32::from_rgb(
+        (color >> 16) as u8,
+        (color >> 8) as u8,
+        color as u8,
+    ))
+}
+
+/// The member's avatar with a status dot in its corner.
+fn avatar(ui: &mut egui::Ui, member: &Member, status: Status) {
+    let user = &member.user;
+    let url = cdn::avatar(user.id, &user.discriminator, user.avatar.as_deref(), 64);
+    let size = egui::vec2(AVATAR_SIZE, AVATAR_SIZE);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    match images::texture(ui.ctx(), &url) {
+        Some(texture) => egui::Image::new(texture.id(), size).paint_at(ui, rect),
+        None => {
+            ui.painter().circle_filled(
+                rect.center(),
+                AVATAR_SIZE / 2.0,
+                ui.visuals().faint_bg_color,
+            );
+        }
+    }
+    status_dot(ui, rect, status);
+}
@@ -0,0 +1,84 @@
+use egui::{Color32, RichText};
+
+use super::markdown;
+use super::time;
+use super::RenderContext;
+use crate::discord::snowflake::Snowflake;
+use crate::{Guild, Message};
+
+/// The red pill with a mention count shown next to a guild's name. Draws
+/// nothing for zero.
+pub fn badge(ui: &mut egui::Ui, count: usize) {
+    if count == 0 {
+        return;
+    }
+    let text = if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    };
+    let font = egui::FontId::proportional(11.0);
+    let galley = ui.fonts().layout_no_wrap(text, font, Color32::WHITE);
+    let size = egui::vec2((galley.size().x + 8.0).max(16.0), 16.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 8.0, Color32::from_rgb(237, 66, 69));
+    painter.galley(rect.center() - galley.size() / 2.0, galley);
+}
+
+/// Lists recent mentions across guilds, newest first. `cx.roles` are those of
+/// `roles_guild`; role mentions in other guilds are left as plain text.
+/// Returns the guild and channel of the entry whose "Jump" button was
+/// clicked.
+pub fn inbox(
+    ui: &mut egui::Ui,
+    messages: &[Message],
+    guilds: &[Guild],
+    roles_guild: Option<Snowflake>,
+    cx: &mut RenderContext,
+) -> Option<(Option<Snowflake>, Snowflake)> {
+    if messages.is_empty() {
+        ui.label(RichText::new("You have no recent mentions.").weak());
+        return None;
+    }
+    let mut jump = None;
+    let roles = cx.roles;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for message in messages {
+            let place = match message.guild_id {
+                Some(id) => guilds
+                    .iter()
+                    .find(|g| g.id == id)
+                    .map(|g| g.name.clone())
+                    .unwrap_or_else(|| "Unknown server".to_string()),
+                None => "Direct Messages".to_string(),
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(place).small().weak());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Jump").clicked() {
+                        jump = Some((message.guild_id, message.channel_id));
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&message.author.username).strong());
+                ui.label(
+                    RichText::new(time::format_relative(&message.timestamp))
+                        .small()
+                        .weak(),
+                )
+                .on_hover_text(time::format_full(&message.timestamp));
+            });
+            cx.roles = if message.guild_id.is_some() && message.guild_id == roles_guild {
+                roles
+            } else {
+                &[]
+            };
+            let id = egui::Id::new(("mention", message.id));
+            markdown::show(ui, id, &message.content, &message.mentions, cx);
+            ui.separator();
+        }
+    });
+    jump
+}
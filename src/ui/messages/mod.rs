@@ -0,0 +1,277 @@
+use chrono::Duration;
+use egui::{Color32, RichText};
+
+use super::attachments;
+use super::components;
+use super::embeds;
+use super::images;
+use super::markdown;
+use super::settings::{MessageDisplay, Settings};
+use super::stickers;
+use super::time;
+use super::{Action, RenderContext};
+use crate::discord::cdn;
+use crate::discord::types::MessageFlags;
+use crate::{Message, MessageAuthor};
+
+/// Consecutive messages from one author closer together than this share a
+/// header.
+const GROUP_WINDOW_MINUTES: i64 = 7;
+
+const AVATAR_SIZE: f32 = 32.0;
+
+pub fn message_list(
+    ui: &mut egui::Ui,
+    messages: &[Message],
+    settings: &Settings,
+    cx: &mut RenderContext,
+) {
+    let mut previous: Option<&Message> = None;
+    for message in messages {
+        let date = time::local_date(&message.timestamp);
+        let new_day = previous.is_none_or(|p| time::local_date(&p.timestamp) != date);
+        if new_day {
+            time::date_separator(ui, date);
+        }
+        let header = new_day || starts_group(previous, message);
+        let mentioned = cx
+            .user_id
+            .is_some_and(|id| message.mentions_user(id, cx.member_roles));
+        let top = ui.cursor().top();
+        highlight(ui, mentioned, |ui| match settings.message_display {
+            MessageDisplay::Cozy => cozy_message(ui, message, header, cx),
+            MessageDisplay::Compact => compact_message(ui, message, header, cx),
+        });
+        if cx.scroll_to == Some(message.id) {
+            let rect =
+                egui::Rect::from_x_y_ranges(ui.max_rect().x_range(), top..=ui.cursor().top());
+            ui.scroll_to_rect(rect, Some(egui::Align::Center));
+            cx.scroll_to = None;
+        }
+        previous = Some(message);
+    }
+}
+
+/// Draws a message on Discord's mention highlight (a tinted background with
+/// a gold bar on the left) when `mentioned` is set.
+fn highlight(ui: &mut egui::Ui, mentioned: bool, add_contents: impl FnOnce(&mut egui::Ui)) {
+    if !mentioned {
+        add_contents(ui);
+        return;
+    }
+    const MENTION_COLOR: Color32 = Color32::from_rgb(250, 168, 26);
+    let response = egui::Frame::none()
+        .fill(MENTION_COLOR.linear_multiply(0.1))
+        .inner_margin(egui::style::Margin {
+            left: 4.0,
+            ..Default::default()
+        })
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            add_contents(ui);
+        })
+        .response;
+    let rect = response.rect;
+    let bar = egui::Rect::from_min_size(rect.min, egui::vec2(2.0, rect.height()));
+    ui.painter().rect_filled(bar, 0.0, MENTION_COLOR);
+}
+
+fn starts_group(previous: Option<&Message>, message: &Message) -> bool {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return true,
+    };
+    previous.author.id != message.author.id
+        || previous.system_text().is_some()
+        || message.system_text().is_some()
+        || message.timestamp - previous.timestamp > Duration::minutes(GROUP_WINDOW_MINUTES)
+}
+
+fn cozy_message(ui: &mut egui::Ui, message: &Message, header: bool, cx: &mut RenderContext) {
+    if let Some(text) = message.system_text() {
+        ui.add_space(4.0);
+        system_line(ui, message, text);
+        return;
+    }
+    if header {
+        ui.add_space(8.0);
+    }
+    let row = ui.horizontal(|ui| {
+        if header {
+            avatar(ui, &message.author);
+        } else {
+            // Keep continuation lines aligned with the header's content.
+            ui.add_space(AVATAR_SIZE + ui.spacing().item_spacing.x);
+        }
+        ui.vertical(|ui| {
+            if header {
+                ui.horizontal(|ui| {
+                    author(ui, message, message.author.username.clone(), cx);
+                    timestamp(ui, message, time::format_relative(&message.timestamp));
+                });
+            }
+            content(ui, message, cx);
+        })
+        .response
+        .on_hover_text(time::format_full(&message.timestamp));
+    });
+    context_menu(row.response, message, cx);
+}
+
+fn compact_message(ui: &mut egui::Ui, message: &Message, header: bool, cx: &mut RenderContext) {
+    if let Some(text) = message.system_text() {
+        system_line(ui, message, text);
+        return;
+    }
+    let row = ui.horizontal_wrapped(|ui| {
+        timestamp(ui, message, time::format_time(&message.timestamp));
+        if header {
+            author(ui, message, format!("{}:", message.author.username), cx);
+        }
+        content(ui, message, cx);
+    });
+    context_menu(row.response, message, cx);
+}
+
+fn context_menu(response: egui::Response, message: &Message, cx: &mut RenderContext) {
+    // Messages that already have a thread link to it instead.
+    let create_thread = cx.threads && message.thread.is_none();
+    if !create_thread && !cx.can_pin {
+        return;
+    }
+    response.context_menu(|ui| {
+        if create_thread && ui.button("Create Thread").clicked() {
+            cx.actions.push(Action::CreateThread(message.id));
+            ui.close_menu();
+        }
+        if cx.can_pin {
+            let (label, action) = if message.pinned {
+                (
+                    "Unpin Message",
+                    Action::Unpin(message.channel_id, message.id),
+                )
+            } else {
+                ("Pin Message", Action::Pin(message.channel_id, message.id))
+            };
+            if ui.button(label).clicked() {
+                cx.actions.push(action);
+                ui.close_menu();
+            }
+        }
+    });
+}
+
+/// The author's name, which opens their profile when clicked.
+fn author(ui: &mut egui::Ui, message: &Message, text: String, cx: &mut RenderContext) {
+    let label = egui::Label::new(RichText::new(text).strong()).sense(egui::Sense::click());
+    if ui.add(label).clicked() {
+        cx.actions.push(Action::OpenUser(message.author.id));
+    }
+}
+
+fn system_line(ui: &mut egui::Ui, message: &Message, text: String) {
+    ui.horizontal_wrapped(|ui| {
+        timestamp(ui, message, time::format_time(&message.timestamp));
+        ui.label(RichText::new(text).italics().weak());
+    });
+}
+
+fn timestamp(ui: &mut egui::Ui, message: &Message, text: String) {
+    ui.label(RichText::new(text).small().weak())
+        .on_hover_text(time::format_full(&message.timestamp));
+}
+
+fn content(ui: &mut egui::Ui, message: &Message, cx: &mut RenderContext) {
+    ui.vertical(|ui| {
+        let id = egui::Id::new(("message", message.id));
+        if let Some(interaction) = &message.interaction {
+            ui.label(
+                RichText::new(format!(
+                    "{} used /{}",
+                    interaction.user.username, interaction.name
+                ))
+                .small()
+                .weak(),
+            );
+        }
+        let flags = message.flags.unwrap_or_default();
+        if flags.contains(MessageFlags::LOADING) {
+            // A deferred response, edited into the real one later.
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(
+                    RichText::new(format!("{} is thinking…", message.author.username))
+                        .italics()
+                        .weak(),
+                );
+            });
+        }
+        if !message.content.is_empty() {
+            markdown::show(ui, id, &message.content, &message.mentions, cx);
+        }
+        if let Some(edited) = &message.edited_timestamp {
+            ui.label(RichText::new("(edited)").small().weak())
+                .on_hover_text(time::format_full(edited));
+        }
+        for attachment in &message.attachments {
+            attachments::attachment(ui, attachment, cx);
+        }
+        stickers::stickers(ui, &message.sticker_items);
+        for (index, embed) in message.embeds.iter().enumerate() {
+            embeds::embed(ui, id.with(("embed", index)), embed, &message.mentions, cx);
+        }
+        components::components(ui, message, cx);
+        if let Some(thread) = &message.thread {
+            let text = format!("🧵 {} · {} messages", thread.name, thread.message_count);
+            if ui.button(RichText::new(text).small()).clicked() {
+                cx.actions.push(Action::OpenThread(thread.id));
+            }
+        }
+        if message.pinned {
+            ui.label(RichText::new("📌 Pinned").small().weak());
+        }
+        if flags.contains(MessageFlags::EPHEMERAL) {
+            ui.label(RichText::new("👁 Only you can see this").small().weak());
+        }
+    });
+}
+
+/// The author's avatar, or while it loads, the first letter of their name on
+/// one of Discord's default avatar colours. Returns where it was drawn.
+pub fn avatar(ui: &mut egui::Ui, author: &MessageAuthor) -> egui::Rect {
+    let url = cdn::avatar(
+        author.id,
+        &author.discriminator,
+        author.avatar.as_deref(),
+        64,
+    );
+    let size = egui::vec2(AVATAR_SIZE, AVATAR_SIZE);
+    if let Some(texture) = images::texture(ui.ctx(), &url) {
+        return ui.image(texture.id(), size).rect;
+    }
+    const COLORS: [Color32; 5] = [
+        Color32::from_rgb(88, 101, 242),
+        Color32::from_rgb(117, 126, 138),
+        Color32::from_rgb(59, 165, 92),
+        Color32::from_rgb(250, 166, 26),
+        Color32::from_rgb(237, 66, 69),
+    ];
+    let index = author.discriminator.parse::<usize>().unwrap_or(0) % COLORS.len();
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter();
+    painter.circle_filled(rect.center(), AVATAR_SIZE / 2.0, COLORS[index]);
+    let initial = author
+        .username
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_default();
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        initial,
+        egui::FontId::proportional(AVATAR_SIZE / 2.0),
+        Color32::WHITE,
+    );
+    rect
+}
@@ -0,0 +1,64 @@
+pub mod attachments;
+pub mod commands;
+pub mod components;
+pub mod direct_messages;
+pub mod embeds;
+pub mod forum;
+pub mod guilds;
+pub mod highlight;
+pub mod images;
+pub mod markdown;
+pub mod members;
+pub mod mentions;
+pub mod messages;
+pub mod pins;
+pub mod presence;
+pub mod profile;
+pub mod settings;
+pub mod stickers;
+pub mod threads;
+pub mod time;
+pub mod typing;
+
+use crate::discord::interactions::ComponentClick;
+use crate::discord::permissions::Role;
+use crate::discord::snowflake::Snowflake;
+use crate::Channel;
+
+/// Something the user asked for by clicking inside a message. Collected while
+/// drawing and handled by the app once the frame's widgets are laid out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    None,
+    OpenChannel(Snowflake),
+    OpenUser(Snowflake),
+    /// Show the image at this URL full size.
+    OpenImage(String),
+    /// Send a button click or menu choice to the bot that owns the message.
+    Interact(ComponentClick),
+    /// Open the thread in the side panel.
+    OpenThread(Snowflake),
+    /// Ask for a name for a thread to start on this message.
+    CreateThread(Snowflake),
+    /// Pin the message (second) in the channel (first).
+    Pin(Snowflake, Snowflake),
+    Unpin(Snowflake, Snowflake),
+}
+
+/// Lookups for resolving ids found in message content, and the actions that
+/// clicks on the resulting widgets produced.
+pub struct RenderContext<'a> {
+    pub channels: &'a [Channel],
+    pub roles: &'a [Role],
+    /// Who we are, and the roles we hold in the guild being shown, for
+    /// highlighting messages that mention us.
+    pub user_id: Option<Snowflake>,
+    pub member_roles: &'a [Snowflake],
+    /// Whether threads can be started on the messages shown.
+    pub threads: bool,
+    /// Whether we may pin and unpin the messages shown.
+    pub can_pin: bool,
+    /// A message to bring into view, cleared once it has been drawn.
+    pub scroll_to: Option<Snowflake>,
+    pub actions: Vec<Action>,
+}
@@ -0,0 +1,51 @@
+use egui::RichText;
+
+use super::{markdown, time, RenderContext};
+use crate::discord::snowflake::Snowflake;
+use crate::Message;
+
+const WIDTH: f32 = 360.0;
+
+/// The contents of the pinned messages popover, most recently pinned first.
+/// Returns the message whose "Jump" was clicked.
+pub fn pins(ui: &mut egui::Ui, messages: &[Message], cx: &mut RenderContext) -> Option<Snowflake> {
+    ui.set_width(WIDTH);
+    ui.label(RichText::new("Pinned Messages").strong());
+    if messages.is_empty() {
+        ui.label(RichText::new("This channel doesn't have any pinned messages yet.").weak());
+        return None;
+    }
+    let mut jump = None;
+    egui::ScrollArea::vertical()
+        .max_height(400.0)
+        .show(ui, |ui| {
+            for message in messages {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&message.author.username).strong());
+                        ui.label(
+                            RichText::new(time::format_relative(&message.timestamp))
+                                .small()
+                                .weak(),
+                        );
+                        if ui.small_button("Jump").clicked() {
+                            jump = Some(message.id);
+                        }
+                    });
+                    if !message.content.is_empty() {
+                        let id = egui::Id::new(("pin", message.id));
+                        markdown::show(ui, id, &message.content, &message.mentions, cx);
+                    }
+                    if !message.attachments.is_empty() {
+                        ui.label(
+                            RichText::new(format!("{} attachments", message.attachments.len()))
+                                .small()
+                                .weak(),
+                        );
+                    }
+                });
+            }
+        });
+    jump
+}
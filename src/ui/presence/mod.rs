@@ -0,0 +1,121 @@
+use chrono::{TimeZone, Utc};
+use egui::RichText;
+
+use super::members::status_color;
+use super::settings::Settings;
+use super::time;
+use crate::discord::presence::CustomStatus;
+use crate::discord::types::Status;
+
+/// How long a new custom status lasts, in minutes.
+const EXPIRY_CHOICES: [(&str, Option<i64>); 4] = [
+    ("Don't clear", None),
+    ("30 minutes", Some(30)),
+    ("1 hour", Some(60)),
+    ("4 hours", Some(4 * 60)),
+];
+
+/// A custom status being typed, kept in egui's temp data until "Set".
+#[derive(Clone, Default)]
+struct Draft {
+    text: String,
+    emoji: String,
+    expiry: usize,
+}
+
+pub fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::Online => "Online",
+        Status::Idle => "Idle",
+        Status::Dnd => "Do Not Disturb",
+        Status::Invisible => "Invisible",
+        Status::Offline => "Offline",
+    }
+}
+
+/// The status menu in the menu bar. Changes go straight into `settings`,
+/// from which the app sends them to the gateway.
+pub fn status_menu(ui: &mut egui::Ui, settings: &mut Settings) {
+    let label = RichText::new(format!("● {}", status_name(settings.status)))
+        .color(status_color(settings.status));
+    ui.menu_button(label, |ui| {
+        for (status, description) in [
+            (Status::Online, ""),
+            (Status::Idle, ""),
+            (Status::Dnd, "You will not receive desktop notifications"),
+            (Status::Invisible, "You will appear offline"),
+        ] {
+            let text =
+                RichText::new(format!("● {}", status_name(status))).color(status_color(status));
+            let response = ui.radio_value(&mut settings.status, status, text);
+            if !description.is_empty() {
+                response.on_hover_text(description);
+            }
+        }
+        ui.separator();
+        custom_status(ui, settings);
+    });
+}
+
+fn custom_status(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.label(RichText::new("CUSTOM STATUS").small().strong());
+    if let Some(current) = settings.custom_status.clone() {
+        let mut clear = false;
+        ui.horizontal(|ui| {
+            let emoji = current.emoji.as_deref().unwrap_or("");
+            ui.label(format!("{} {}", emoji, current.text));
+            clear = ui.small_button("Clear").clicked();
+        });
+        if clear {
+            settings.custom_status = None;
+        }
+        if let Some(at) = current
+            .expires_at
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        {
+            ui.label(
+                RichText::new(format!("Clears at {}", time::format_time(&at)))
+                    .small()
+                    .weak(),
+            );
+        }
+        ui.separator();
+    }
+    let id = egui::Id::new("custom_status_draft");
+    let mut draft = ui.data().get_temp::<Draft>(id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut draft.emoji)
+                .hint_text("🙂")
+                .desired_width(24.0),
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut draft.text)
+                .hint_text("What's happening?")
+                .desired_width(160.0),
+        );
+    });
+    egui::ComboBox::from_id_source("custom_status_expiry")
+        .selected_text(EXPIRY_CHOICES[draft.expiry].0)
+        .show_ui(ui, |ui| {
+            for (index, (name, _)) in EXPIRY_CHOICES.iter().enumerate() {
+                ui.selectable_value(&mut draft.expiry, index, *name);
+            }
+        });
+    let status = CustomStatus {
+        text: draft.text.trim().to_string(),
+        emoji: Some(draft.emoji.trim().to_string()).filter(|e| !e.is_empty()),
+        expires_at: EXPIRY_CHOICES[draft.expiry]
+            .1
+            .map(|minutes| Utc::now().timestamp_millis() + minutes * 60 * 1000),
+    };
+    if ui
+        .add_enabled(!status.is_empty(), egui::Button::new("Set"))
+        .clicked()
+    {
+        settings.custom_status = Some(status);
+        draft = Draft::default();
+        ui.close_menu();
+    }
+    ui.data().insert_temp(id, draft);
+}
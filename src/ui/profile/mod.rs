@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use egui::{Color32, RichText};
+
+use super::{images, members, presence, time};
+use crate::discord::cdn;
+use crate::discord::permissions::Role;
+use crate::discord::presence::UserPresence;
+use crate::discord::types::{ActivityType, UserFlags};
+use crate::{Guild, UserProfile};
+
+const AVATAR_SIZE: f32 = 80.0;
+
+/// The badges Discord shows on profiles, in its order.
+const BADGES: [(UserFlags, &str); 14] = [
+    (UserFlags::STAFF, "Discord Staff"),
+    (UserFlags::PARTNER, "Partnered Server Owner"),
+    (UserFlags::CERTIFIED_MODERATOR, "Moderator Programs Alumni"),
+    (UserFlags::HYPESQUAD, "HypeSquad Events"),
+    (UserFlags::HYPESQUAD_ONLINE_HOUSE_1, "HypeSquad Bravery"),
+    (UserFlags::HYPESQUAD_ONLINE_HOUSE_2, "HypeSquad Brilliance"),
+    (UserFlags::HYPESQUAD_ONLINE_HOUSE_3, "HypeSquad Balance"),
+    (UserFlags::BUG_HUNTER_LEVEL_1, "Bug Hunter"),
+    (UserFlags::BUG_HUNTER_LEVEL_2, "Bug Hunter Gold"),
+    (UserFlags::ACTIVE_DEVELOPER, "Active Developer"),
+    (
+        UserFlags::VERIFIED_DEVELOPER,
+        "Early Verified Bot Developer",
+    ),
+    (UserFlags::PREMIUM_EARLY_SUPPORTER, "Early Supporter"),
+    (UserFlags::VERIFIED_BOT, "Verified Bot"),
+    (UserFlags::BOT_HTTP_INTERACTIONS, "Supports Commands"),
+];
+
+pub fn badges(flags: UserFlags) -> Vec<&'static str> {
+    BADGES
+        .iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// The body of the profile popout. `roles` are the current guild's, for
+/// naming the member's. Returns whether "Message" was clicked.
+pub fn profile(
+    ui: &mut egui::Ui,
+    profile: &UserProfile,
+    guilds: &[Guild],
+    roles: &[Role],
+    user_presence: Option<&UserPresence>,
+) -> bool {
+    let status = user_presence.map(|p| p.status).unwrap_or_default();
+    let user = &profile.user;
+    let mut message = false;
+    ui.horizontal(|ui| {
+        let url = cdn::avatar(user.id, &user.discriminator, user.avatar.as_deref(), 128);
+        let avatar = images::show(ui, &url, egui::vec2(AVATAR_SIZE, AVATAR_SIZE));
+        members::status_dot(ui, avatar.rect, status);
+        ui.vertical(|ui| {
+            let display_name = user.global_name.as_deref().unwrap_or(&user.username);
+            ui.heading(display_name);
+            // Users on the new username system have no discriminator.
+            let handle = match user.discriminator.as_str() {
+                "0" => user.username.clone(),
+                discriminator => format!("{}#{}", user.username, discriminator),
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(handle).weak());
+                if user.bot {
+                    ui.label(RichText::new("BOT").small().strong());
+                }
+            });
+            if let Some(nick) = profile.member.as_ref().and_then(|m| m.nick.as_ref()) {
+                ui.label(RichText::new(format!("Nickname: {}", nick)).small());
+            }
+            ui.label(
+                RichText::new(format!("● {}", presence::status_name(status)))
+                    .small()
+                    .color(members::status_color(status)),
+            );
+            message = ui.button("Message").clicked();
+        });
+    });
+    let badges = badges(user.public_flags);
+    if !badges.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            for badge in badges {
+                ui.label(RichText::new(badge).small().strong());
+            }
+        });
+    }
+    ui.separator();
+
+    let activities = user_presence
+        .filter(|p| p.status.is_online())
+        .map(|p| p.activities.as_slice())
+        .unwrap_or_default();
+    if !activities.is_empty() {
+        ui.label(RichText::new("ACTIVITY").small().strong());
+        for activity in activities {
+            ui.label(activity.describe());
+            if let Some(details) = activity
+                .details
+                .as_ref()
+                .filter(|_| activity.activity_type != ActivityType::Streaming)
+            {
+                ui.label(RichText::new(details).small().weak());
+            }
+        }
+        ui.add_space(4.0);
+    }
+
+    ui.label(RichText::new("MEMBER SINCE").small().strong());
+    ui.label(format!(
+        "Discord: {}",
+        time::format_full(&DateTime::<Utc>::from(user.id.created_at()))
+    ));
+    if let Some(member) = &profile.member {
+        ui.label(format!(
+            "This server: {}",
+            time::format_full(&member.joined_at)
+        ));
+
+        let mut member_roles: Vec<&Role> = roles
+            .iter()
+            .filter(|r| member.roles.contains(&r.id))
+            .collect();
+        if !member_roles.is_empty() {
+            member_roles.sort_by_key(|r| std::cmp::Reverse(r.position));
+            ui.add_space(4.0);
+            ui.label(RichText::new("ROLES").small().strong());
+            ui.horizontal_wrapped(|ui| {
+                for role in member_roles {
+                    role_chip(ui, role);
+                }
+            });
+        }
+    }
+
+    ui.add_space(4.0);
+    ui.label(RichText::new("MUTUAL SERVERS").small().strong());
+    match &profile.mutual_guilds {
+        Some(ids) if ids.is_empty() => {
+            ui.label(RichText::new("None").weak());
+        }
+        Some(ids) => {
+            for guild in guilds.iter().filter(|g| ids.contains(&g.id)) {
+                ui.label(&guild.name);
+            }
+        }
+        None => {
+            ui.label(RichText::new("Not available").weak());
+        }
+    }
+    message
+}
+
+fn role_chip(ui: &mut egui::Ui, role: &Role) {
+    let color = match role.color {
+        0 => ui.visuals().weak_text_color(),
+        color => Color32::from_rgb((color >> 16) as u8, (color >> 8) as u8, color as u8),
+    };
+    egui::Frame::none()
+        .fill(ui.visuals().faint_bg_color)
+        .inner_margin(egui::style::Margin::symmetric(6.0, 2.0))
+        .rounding(8.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(8.0, 8.0), egui::Sense::hover());
+                ui.painter().circle_filled(rect.center(), 4.0, color);
+                ui.label(RichText::new(&role.name).small());
+            });
+        });
+}
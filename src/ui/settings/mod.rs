@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use crate::discord::presence::CustomStatus;
+use crate::discord::types::Status;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageDisplay {
+    /// Avatar and name above each group of messages.
+    #[default]
+    Cozy,
+    /// No avatars, every line prefixed with its time.
+    Compact,
+}
+
+/// User preferences, persisted alongside the token.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub message_display: MessageDisplay,
+    /// The status picked in the status menu.
+    pub status: Status,
+    pub custom_status: Option<CustomStatus>,
+    /// Minutes without input before an online status turns idle; 0 never.
+    pub idle_after_minutes: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            message_display: MessageDisplay::default(),
+            status: Status::Online,
+            custom_status: None,
+            idle_after_minutes: 10,
+        }
+    }
+}
+
+pub fn settings_window(ctx: &egui::Context, open: &mut bool, settings: &mut Settings) {
+    egui::Window::new("Settings")
+        .open(open)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.heading("Message display");
+            ui.radio_value(
+                &mut settings.message_display,
+                MessageDisplay::Cozy,
+                "Cozy: avatars and names above each group",
+            );
+            ui.radio_value(
+                &mut settings.message_display,
+                MessageDisplay::Compact,
+                "Compact: fit more messages on screen",
+            );
+            ui.heading("Status");
+            ui.horizontal(|ui| {
+                ui.label("Go idle after");
+                ui.add(
+                    egui::DragValue::new(&mut settings.idle_after_minutes)
+                        .clamp_range(0..=120)
+                        .suffix(" min"),
+                );
+            });
+            ui.label(
+                egui::RichText::new("Set to 0 to stay online while away.")
+                    .small()
+                    .weak(),
+            );
+        });
+}
@@ -0,0 +1,117 @@
+use egui::RichText;
+
+use super::images;
+use crate::discord::cdn;
+use crate::discord::snowflake::Snowflake;
+use crate::discord::types::StickerFormat;
+use crate::{Sticker, StickerItem};
+
+const STICKER_SIZE: f32 = 160.0;
+const PICKER_SIZE: f32 = 72.0;
+/// What to ask the CDN for, which only serves powers of two: the next size up
+/// from each of the above.
+const STICKER_FETCH_SIZE: u32 = 256;
+const PICKER_FETCH_SIZE: u32 = 128;
+const PICKER_COLUMNS: usize = 5;
+
+/// Draws a message's stickers at Discord's size. APNG stickers show their
+/// first frame.
+pub fn stickers(ui: &mut egui::Ui, items: &[StickerItem]) {
+    for item in items {
+        sticker(
+            ui,
+            item.id,
+            &item.name,
+            item.format_type,
+            STICKER_SIZE,
+            STICKER_FETCH_SIZE,
+        )
+        .on_hover_text(&item.name);
+    }
+}
+
+fn sticker(
+    ui: &mut egui::Ui,
+    id: Snowflake,
+    name: &str,
+    format: StickerFormat,
+    size: f32,
+    fetch_size: u32,
+) -> egui::Response {
+    let size = egui::vec2(size, size);
+    match cdn::sticker(id, format, fetch_size) {
+        Some(url) => images::show(ui, &url, size),
+        None => placeholder(ui, name, size),
+    }
+}
+
+/// Stands in for Lottie stickers, which are vector animations we cannot
+/// play.
+fn placeholder(ui: &mut egui::Ui, name: &str, size: egui::Vec2) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 8.0, ui.visuals().faint_bg_color);
+    painter.text(
+        rect.center() - egui::vec2(0.0, size.y / 8.0),
+        egui::Align2::CENTER_CENTER,
+        "🎞",
+        egui::FontId::proportional(size.y / 4.0),
+        ui.visuals().weak_text_color(),
+    );
+    painter.text(
+        rect.center() + egui::vec2(0.0, size.y / 5.0),
+        egui::Align2::CENTER_CENTER,
+        name,
+        egui::FontId::proportional(12.0),
+        ui.visuals().text_color(),
+    );
+    response
+}
+
+/// A grid of the guild's stickers. Returns the one that was clicked.
+pub fn picker(ui: &mut egui::Ui, stickers: &[Sticker]) -> Option<Snowflake> {
+    let mut picked = None;
+    egui::Frame::none()
+        .fill(ui.visuals().faint_bg_color)
+        .inner_margin(egui::style::Margin::same(6.0))
+        .rounding(4.0)
+        .show(ui, |ui| {
+            if stickers.is_empty() {
+                ui.label(RichText::new("This server has no stickers").weak());
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(PICKER_SIZE * 2.5)
+                .show(ui, |ui| {
+                    egui::Grid::new("sticker_picker").show(ui, |ui| {
+                        for (index, sticker) in stickers.iter().enumerate() {
+                            let response = ui
+                                .add_enabled_ui(sticker.available, |ui| {
+                                    self::sticker(
+                                        ui,
+                                        sticker.id,
+                                        &sticker.name,
+                                        sticker.format_type,
+                                        PICKER_SIZE,
+                                        PICKER_FETCH_SIZE,
+                                    )
+                                })
+                                .inner;
+                            let hover = match &sticker.description {
+                                Some(description) if !description.is_empty() => {
+                                    format!("{}\n{}", sticker.name, description)
+                                }
+                                _ => sticker.name.clone(),
+                            };
+                            if response.on_hover_text(hover).clicked() && sticker.available {
+                                picked = Some(sticker.id);
+                            }
+                            if index % PICKER_COLUMNS == PICKER_COLUMNS - 1 {
+                                ui.end_row();
+                            }
+                        }
+                    });
+                });
+        });
+    picked
+}
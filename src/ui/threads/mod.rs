@@ -0,0 +1,132 @@
+use egui::RichText;
+
+use super::time;
+use crate::discord::read_state::ReadStates;
+use crate::discord::snowflake::Snowflake;
+use crate::discord::threads::{Thread, Threads, AUTO_ARCHIVE_DURATIONS};
+
+/// What the thread panel's header asked for.
+pub enum HeaderAction {
+    Close,
+    SetArchived(bool),
+}
+
+/// A channel's active threads, indented under it in the channel panel, with
+/// the ones we have not joined dimmed. Returns the one that was clicked.
+pub fn list(
+    ui: &mut egui::Ui,
+    threads: &Threads,
+    parent_id: Snowflake,
+    open: Option<Snowflake>,
+    read_states: &ReadStates,
+) -> Option<Snowflake> {
+    let mut clicked = None;
+    for thread in threads.under(parent_id) {
+        ui.horizontal(|ui| {
+            ui.add_space(12.0);
+            let mut name = RichText::new(format!("└ {}", thread.name));
+            if read_states.is_unread(thread.id) {
+                name = name.strong();
+            } else if !threads.is_joined(thread.id) {
+                name = name.weak();
+            }
+            if ui.selectable_label(open == Some(thread.id), name).clicked() {
+                clicked = Some(thread.id);
+            }
+        });
+    }
+    clicked
+}
+
+/// One line of the thread browser: the name, how busy it is and, for
+/// archived threads, when it was archived.
+fn row(ui: &mut egui::Ui, thread: &Thread) -> bool {
+    ui.horizontal(|ui| {
+        let clicked = ui.selectable_label(false, &thread.name).clicked();
+        let mut details = format!("{} messages", thread.message_count);
+        if let Some(metadata) = thread.thread_metadata.as_ref().filter(|m| m.archived) {
+            details.push_str(&format!(
+                " · archived {}",
+                time::format_relative(&metadata.archive_timestamp)
+            ));
+        }
+        ui.label(RichText::new(details).small().weak());
+        clicked
+    })
+    .inner
+}
+
+/// The body of the thread browser: the channel's active threads, then the
+/// archived ones loaded so far. Returns the thread that was clicked.
+pub fn browser(ui: &mut egui::Ui, active: &[&Thread], archived: &[Thread]) -> Option<Snowflake> {
+    let mut clicked = None;
+    ui.label(RichText::new("ACTIVE").small().strong());
+    if active.is_empty() {
+        ui.label(RichText::new("No active threads").weak());
+    }
+    for thread in active {
+        if row(ui, thread) {
+            clicked = Some(thread.id);
+        }
+    }
+    ui.add_space(4.0);
+    ui.label(RichText::new("ARCHIVED").small().strong());
+    for thread in archived {
+        if row(ui, thread) {
+            clicked = Some(thread.id);
+        }
+    }
+    clicked
+}
+
+/// The top of the thread panel. `can_archive` is for the thread's owner and
+/// members who can manage threads.
+pub fn header(ui: &mut egui::Ui, thread: &Thread, can_archive: bool) -> Option<HeaderAction> {
+    let mut action = None;
+    ui.horizontal(|ui| {
+        ui.heading(&thread.name);
+        if ui.small_button("✕").on_hover_text("Close").clicked() {
+            action = Some(HeaderAction::Close);
+        }
+    });
+    ui.horizontal(|ui| {
+        let mut status = format!(
+            "{} messages · {} members",
+            thread.message_count, thread.member_count
+        );
+        if thread.is_locked() {
+            status.push_str(" · locked");
+        } else if thread.is_archived() {
+            status.push_str(" · archived");
+        }
+        ui.label(RichText::new(status).small().weak());
+        let archived = thread.is_archived();
+        let label = if archived { "Unarchive" } else { "Archive" };
+        if ui
+            .add_enabled(can_archive, egui::Button::new(label).small())
+            .clicked()
+        {
+            action = Some(HeaderAction::SetArchived(!archived));
+        }
+    });
+    action
+}
+
+/// The "Create Thread" form. Returns whether "Create" was clicked.
+pub fn create_form(ui: &mut egui::Ui, name: &mut String, auto_archive_duration: &mut u64) -> bool {
+    ui.add(egui::TextEdit::singleline(name).hint_text("Thread name"));
+    let selected = AUTO_ARCHIVE_DURATIONS
+        .iter()
+        .find(|(minutes, _)| minutes == auto_archive_duration)
+        .map(|(_, label)| *label)
+        .unwrap_or_default();
+    egui::ComboBox::from_label("Hide after inactivity")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (minutes, label) in AUTO_ARCHIVE_DURATIONS {
+                ui.selectable_value(auto_archive_duration, minutes, label);
+            }
+        });
+    ui.add_enabled(!name.trim().is_empty(), egui::Button::new("Create"))
+        .clicked()
+}
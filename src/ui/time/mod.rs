@@ -0,0 +1,84 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+
+/// Short form used next to messages: "Today at 14:03", "Yesterday at 09:12",
+/// or the date for anything older.
+pub fn format_relative(timestamp: &DateTime<Utc>) -> String {
+    let local = timestamp.with_timezone(&Local);
+    let today = Local::now().date_naive();
+    let date = local.date_naive();
+    if date == today {
+        format!("Today at {}", local.format("%H:%M"))
+    } else if today.pred_opt() == Some(date) {
+        format!("Yesterday at {}", local.format("%H:%M"))
+    } else {
+        local.format("%d/%m/%Y").to_string()
+    }
+}
+
+/// Just the local time of day, for messages that follow a header.
+pub fn format_time(timestamp: &DateTime<Utc>) -> String {
+    timestamp.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+/// Full form shown in tooltips: "Wednesday, 14 September 2022 14:03".
+pub fn format_full(timestamp: &DateTime<Utc>) -> String {
+    timestamp
+        .with_timezone(&Local)
+        .format("%A, %-d %B %Y %H:%M")
+        .to_string()
+}
+
+pub fn local_date(timestamp: &DateTime<Utc>) -> NaiveDate {
+    timestamp.with_timezone(&Local).date_naive()
+}
+
+/// A horizontal rule with the date in the middle, drawn between messages
+/// sent on different days.
+pub fn date_separator(ui: &mut egui::Ui, date: NaiveDate) {
+    ui.horizontal(|ui| {
+        let text = egui::RichText::new(date.format("%-d %B %Y").to_string())
+            .small()
+            .weak();
+        let width = (ui.available_width() - 120.0).max(0.0) / 2.0;
+        ui.add_sized([width, 12.0], egui::Separator::default().horizontal());
+        ui.label(text);
+        ui.add_sized([width, 12.0], egui::Separator::default().horizontal());
+    });
+}
+
+/// Renders a `<t:unix:style>` tag the way Discord does, returning the inline
+/// text and the full form for its tooltip. `None` if the time is out of range.
+pub fn format_tag(unix: i64, style: Option<char>) -> Option<(String, String)> {
+    let timestamp = Utc.timestamp_opt(unix, 0).single()?;
+    let local = timestamp.with_timezone(&Local);
+    let text = match style.unwrap_or('f') {
+        't' => local.format("%H:%M").to_string(),
+        'T' => local.format("%H:%M:%S").to_string(),
+        'd' => local.format("%d/%m/%Y").to_string(),
+        'D' => local.format("%-d %B %Y").to_string(),
+        'F' => local.format("%A, %-d %B %Y %H:%M").to_string(),
+        'R' => format_ago(timestamp - Utc::now()),
+        _ => local.format("%-d %B %Y %H:%M").to_string(),
+    };
+    Some((text, format_full(&timestamp)))
+}
+
+/// "in 3 days" / "5 minutes ago", using the largest unit that fits.
+fn format_ago(offset: Duration) -> String {
+    let seconds = offset.num_seconds();
+    let magnitude = seconds.unsigned_abs();
+    let (count, unit) = match magnitude {
+        0..=59 => (magnitude, "second"),
+        60..=3_599 => (magnitude / 60, "minute"),
+        3_600..=86_399 => (magnitude / 3_600, "hour"),
+        86_400..=2_591_999 => (magnitude / 86_400, "day"),
+        2_592_000..=31_535_999 => (magnitude / 2_592_000, "month"),
+        _ => (magnitude / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    if seconds < 0 {
+        format!("{} {}{} ago", count, unit, plural)
+    } else {
+        format!("in {} {}{}", count, unit, plural)
+    }
+}
@@ -0,0 +1,20 @@
+use egui::RichText;
+
+/// Past this many names the indicator stops listing them.
+const MAX_NAMES: usize = 3;
+
+/// "alice is typing…" and so on for the people typing in the open channel.
+/// Keeps its line even when empty so the layout does not jump.
+pub fn indicator(ui: &mut egui::Ui, names: &[&str]) {
+    let text = match names {
+        [] => String::new(),
+        [name] => format!("{} is typing…", name),
+        [first, second] => format!("{} and {} are typing…", first, second),
+        names if names.len() <= MAX_NAMES => {
+            let (last, rest) = names.split_last().unwrap();
+            format!("{} and {} are typing…", rest.join(", "), last)
+        }
+        _ => "Several people are typing…".to_string(),
+    };
+    ui.label(RichText::new(text).small().weak());
+}